@@ -5,23 +5,57 @@
 
 pub mod on_add_row;
 pub mod on_changed_effort;
+pub mod on_check_cell;
 pub mod on_del_row;
+pub mod on_export_anonymized;
+pub mod on_export_csv;
+pub mod on_export_html;
+pub mod on_export_ics;
+pub mod on_export_xlsx;
 pub mod on_hide_dev;
+pub mod on_import_csv;
+pub mod on_import_ics;
+pub mod on_import_msproject;
+pub mod on_import_projects;
+pub mod on_merge;
 pub mod on_move_effort;
 pub mod on_new_project;
+pub mod on_rename_worker;
+pub mod on_roster;
 pub mod on_save_file;
 pub mod on_search;
 pub mod on_set_dev_effort;
+pub mod on_set_italian_holidays;
+pub mod on_validate;
+pub mod populate_models;
 pub mod rebuild_project;
+pub mod update_sovra_week;
 
 // Re-export commonly used functions
 pub use on_add_row::register_on_add_row;
 pub use on_changed_effort::register_on_changed_effort;
+pub use on_check_cell::register_on_check_cell;
 pub use on_del_row::register_on_del_row;
+pub use on_export_anonymized::register_on_export_anonymized;
+pub use on_export_csv::register_on_export_csv;
+pub use on_export_html::register_on_export_html;
+pub use on_export_ics::register_on_export_ics;
+pub use on_export_xlsx::register_on_export_xlsx;
 pub use on_hide_dev::register_on_hide_dev;
+pub use on_import_csv::register_on_import_csv;
+pub use on_import_ics::register_on_import_ics;
+pub use on_import_msproject::register_on_import_msproject;
+pub use on_import_projects::register_on_import_projects;
+pub use on_merge::register_on_merge;
 pub use on_move_effort::register_on_move_effort;
 pub use on_new_project::register_on_new_project;
+pub use on_rename_worker::register_on_rename_worker;
+pub use on_roster::register_on_roster;
 pub use on_save_file::register_on_save_file;
 pub use on_search::register_on_search;
 pub use on_set_dev_effort::register_on_set_dev_effort;
+pub use on_set_italian_holidays::register_on_set_italian_holidays;
+pub use on_validate::register_on_validate;
+pub use populate_models::{PlanModels, populate_models};
 pub use rebuild_project::rebuild_project;
+pub use update_sovra_week::update_sovra_week;
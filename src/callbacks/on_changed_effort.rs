@@ -1,13 +1,12 @@
 //! Callback handler for effort changes.
 
 use slint::{ComponentHandle, Global, Model, SharedString, VecModel};
-use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::{
-    AppWindow, EffortByDateData, EffortByPrjData, PjmCallback, SovraData,
-    callbacks::rebuild_project,
-    models::{DevId, ProjectId, SovraDto, effort_by_date::EffortByDateDataExt},
+    AppWindow, EffortByDateData, EffortByPrjData, HolidayData, PjmCallback, SovraData,
+    callbacks::{rebuild_project, update_sovra_week},
+    models::{CalendarDto, DevId, ProjectId, SovraDto},
     utils::info_cell,
 };
 
@@ -23,11 +22,15 @@ use crate::{
 /// * `vec_model_projects` - Project data model
 /// * `vec_model_worker_names` - Worker names model
 /// * `vec_model_sovra` - Over-allocation tracking model
+/// * `vec_model_week_off` - Closure weeks model
+/// * `vec_model_holidays` - Holidays model
 pub fn register_on_changed_effort(
     ui: &AppWindow,
     vec_model_projects: Rc<VecModel<EffortByPrjData>>,
     vec_model_worker_names: Rc<VecModel<SharedString>>,
     vec_model_sovra: Rc<VecModel<SovraData>>,
+    vec_model_week_off: Rc<VecModel<i32>>,
+    vec_model_holidays: Rc<VecModel<HolidayData>>,
 ) {
     let ui_weak = ui.as_weak();
 
@@ -56,39 +59,20 @@ pub fn register_on_changed_effort(
             DevId(effort.dev as usize),
         );
 
-        // Calculate over-allocation for this week
-        let mut sovra_hash = HashMap::new();
-        for p in 0..vec_model_projects.row_count() {
-            let effort_by_prj_data = vec_model_projects.row_data(p).unwrap();
-            for d in 0..effort_by_prj_data.efforts.row_count() {
-                let effort_by_dev_data = effort_by_prj_data.efforts.row_data(d).unwrap();
-                for data in 0..effort_by_dev_data.datas.row_count() {
-                    let effort_by_date_data = effort_by_dev_data.datas.row_data(data).unwrap();
-                    if effort_by_date_data.week == effort.week {
-                        effort_by_date_data.get_sovra(&mut sovra_hash);
-                        break;
-                    }
-                }
-            }
-        }
-
-        println!("sovra: {:?}", sovra_hash);
-
-        // Update sovra data for this week
-        for s in 0..vec_model_sovra.row_count() {
-            let mut sovra: SovraDto = vec_model_sovra.row_data(s).unwrap().into();
-            if sovra.week == effort.week {
-                for n in 0..vec_model_worker_names.row_count() {
-                    let name = vec_model_worker_names.row_data(n).unwrap().to_string();
-                    if let Some(val) = sovra.value.get_mut(n) {
-                        *val = *sovra_hash.get(&name).unwrap_or(&0);
-                    }
-                }
-
-                vec_model_sovra.set_row_data(s, sovra.into());
-                break;
-            }
-        }
+        // Recalculate over-allocation for this week
+        let calendar = ui_weak
+            .upgrade()
+            .map(|ui| CalendarDto::from(ui.get_efforts().calendar))
+            .unwrap_or_default();
+        update_sovra_week(
+            &vec_model_projects,
+            &vec_model_worker_names,
+            &vec_model_sovra,
+            &vec_model_week_off,
+            &vec_model_holidays,
+            &calendar,
+            effort.week,
+        );
 
         if let Some(ui) = ui_weak.upgrade() {
             PjmCallback::get(&ui).set_changed(true);
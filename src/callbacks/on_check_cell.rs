@@ -0,0 +1,154 @@
+//! Callback handlers checking and completing the grid cells.
+
+use slint::{Global, Model, ModelRc, SharedString, VecModel};
+use std::rc::Rc;
+
+use crate::{
+    AcceptedCell, AppWindow, CellCheck, PjmCallback,
+    validate::check_cell,
+    workers::{edit_cell, similar_workers, suggest_workers},
+};
+
+/// Asks whether to keep an unreadable cell.
+fn confirm_unreadable(cell: &str, tooltip: &str) -> bool {
+    rfd::MessageDialog::new()
+        .set_title("Cella non valida")
+        .set_level(rfd::MessageLevel::Warning)
+        .set_description(format!("\"{}\": {}.\n\nTenerla comunque?", cell, tooltip))
+        .set_buttons(rfd::MessageButtons::YesNo)
+        .show()
+        == rfd::MessageDialogResult::Yes
+}
+
+/// Asks whether `worker` is really a new worker, offering the closest known
+/// name instead. Returns the worker to use, `None` to keep editing.
+fn confirm_new_worker(worker: &str, similar: &[String]) -> Option<String> {
+    let Some(best) = similar.first() else {
+        let add = rfd::MessageDialog::new()
+            .set_title("Nuovo worker")
+            .set_level(rfd::MessageLevel::Info)
+            .set_description(format!(
+                "\"{}\" non è nell'elenco dei worker.\n\nAggiungerlo come nuovo worker?",
+                worker
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        return (add == rfd::MessageDialogResult::Yes).then(|| worker.to_string());
+    };
+
+    match rfd::MessageDialog::new()
+        .set_title("Nuovo worker")
+        .set_level(rfd::MessageLevel::Warning)
+        .set_description(format!(
+            "\"{}\" non è nell'elenco dei worker.\nForse intendevi: {}?\n\n\
+             Sì: usa \"{}\"\nNo: aggiungi \"{}\" come nuovo worker\nAnnulla: continua a modificare",
+            worker,
+            similar.join(", "),
+            best,
+            worker
+        ))
+        .set_buttons(rfd::MessageButtons::YesNoCancel)
+        .show()
+    {
+        rfd::MessageDialogResult::Yes => Some(best.clone()),
+        rfd::MessageDialogResult::No => Some(worker.to_string()),
+        _ => None,
+    }
+}
+
+/// Registers the cell check and completion callbacks.
+///
+/// `check_cell` tells each `Cell-RW` whether its text is unreadable (counted as
+/// 0%), out of range or names an unknown worker, so that the cell is coloured
+/// and explains why on hover. While editing, `suggest_workers` and `edit_cell`
+/// fill the list of known workers and the percent picker. `accept_cell` asks
+/// for confirmation before an unreadable edit is committed or a new worker is
+/// added, suggesting the known names that look like it.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `vec_model_worker_names` - Worker names model, the known workers
+pub fn register_on_check_cell(ui: &AppWindow, vec_model_worker_names: Rc<VecModel<SharedString>>) {
+    {
+        let vec_model_worker_names = vec_model_worker_names.clone();
+        PjmCallback::get(ui).on_check_cell(move |cell: SharedString| {
+            // Checked again when a worker is added or the plan is reloaded
+            vec_model_worker_names
+                .model_tracker()
+                .track_row_count_changes();
+
+            match check_cell(&cell, |name| {
+                vec_model_worker_names.iter().any(|w| w.as_str() == name)
+            }) {
+                Some(problem) => CellCheck {
+                    text: problem.tooltip().into(),
+                    unreadable: problem.is_unreadable(),
+                },
+                None => CellCheck::default(),
+            }
+        });
+    }
+
+    {
+        let vec_model_worker_names = vec_model_worker_names.clone();
+        PjmCallback::get(ui).on_suggest_workers(move |cell: SharedString| {
+            vec_model_worker_names
+                .model_tracker()
+                .track_row_count_changes();
+
+            let names: Vec<SharedString> = suggest_workers(&cell, vec_model_worker_names.iter())
+                .into_iter()
+                .map(SharedString::from)
+                .collect();
+            ModelRc::new(VecModel::from(names))
+        });
+    }
+
+    PjmCallback::get(ui).on_edit_cell(
+        move |cell: SharedString, worker: SharedString, percent: i32| {
+            edit_cell(
+                &cell,
+                Some(worker.as_str()).filter(|w| !w.is_empty()),
+                Some(percent).filter(|p| *p >= 0),
+            )
+            .into()
+        },
+    );
+
+    PjmCallback::get(ui).on_accept_cell(move |cell: SharedString| {
+        let keep_editing = AcceptedCell {
+            accepted: false,
+            text: cell.clone(),
+        };
+        let known = |name: &str| vec_model_worker_names.iter().any(|w| w.as_str() == name);
+
+        let text = match check_cell(&cell, known) {
+            Some(problem) if problem.is_unreadable() => {
+                println!("on_accept_cell {:?}: {:?}", cell, problem);
+                if !confirm_unreadable(&cell, &problem.tooltip()) {
+                    return keep_editing;
+                }
+                cell.to_string()
+            }
+            _ => match crate::utils::info_cell(&cell) {
+                Some((worker, _)) if !worker.is_empty() && !known(worker) => {
+                    let similar = similar_workers(worker, vec_model_worker_names.iter());
+                    println!(
+                        "on_accept_cell new worker {:?}, similar: {:?}",
+                        worker, similar
+                    );
+                    match confirm_new_worker(worker, &similar) {
+                        Some(name) => edit_cell(&cell, Some(&name), None),
+                        None => return keep_editing,
+                    }
+                }
+                _ => cell.to_string(),
+            },
+        };
+
+        AcceptedCell {
+            accepted: true,
+            text: text.into(),
+        }
+    });
+}
@@ -0,0 +1,61 @@
+//! Callback handler for exporting an anonymized copy of the plan.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    exporters::{AnonymizeOptions, Pseudonym, export_anonymized},
+    models::EffortsDto,
+};
+
+/// Registers the export anonymized callback.
+///
+/// This callback asks for a destination file and writes a copy of the plan with
+/// pseudonyms (or team roles) instead of the workers and, optionally, masked
+/// project names. The mapping to restore the real names is written next to it
+/// as `<name>.mapping.json`. Exporting is allowed in read-only mode too.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+pub fn register_on_export_anonymized(ui: &AppWindow) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_export_anonymized(move |roles: bool, mask_projects: bool| {
+        println!(
+            "on_export_anonymized - roles: {}, mask_projects: {}",
+            roles, mask_projects
+        );
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON files", &["json"])
+            .set_title("Esporta piano anonimo")
+            .set_file_name("efforts-anonymized.json")
+            .save_file()
+        else {
+            return;
+        };
+
+        let options = AnonymizeOptions {
+            pseudonym: if roles {
+                Pseudonym::Role
+            } else {
+                Pseudonym::Numbered
+            },
+            mask_projects,
+        };
+        let mapping_path = path.with_extension("mapping.json");
+        let efforts = EffortsDto::from(ui.get_efforts());
+        if let Err(e) = export_anonymized(
+            &efforts,
+            &options,
+            &path.to_string_lossy(),
+            &mapping_path.to_string_lossy(),
+        ) {
+            println!("Error during export the file {:?}: {}", path, e);
+        }
+    });
+}
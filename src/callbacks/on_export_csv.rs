@@ -0,0 +1,52 @@
+//! Callback handler for exporting the allocation grid to CSV.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    exporters::{CsvLayout, CsvOptions, export_csv},
+    models::EffortsDto,
+};
+
+/// Registers the export CSV callback.
+///
+/// This callback asks for a destination file and writes the current plan as CSV,
+/// either as a flat list or as a matrix with one column per week. Exporting is
+/// allowed in read-only mode too.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+pub fn register_on_export_csv(ui: &AppWindow) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_export_csv(move |matrix: bool, italian: bool| {
+        println!("on_export_csv - matrix: {} - italian: {}", matrix, italian);
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+
+        let path = rfd::FileDialog::new()
+            .add_filter("CSV files", &["csv"])
+            .set_title("Esporta CSV")
+            .set_file_name(if matrix { "efforts_matrix.csv" } else { "efforts.csv" })
+            .save_file();
+
+        if let Some(path) = path {
+            let efforts = EffortsDto::from(ui.get_efforts());
+            let layout = if matrix {
+                CsvLayout::Matrix
+            } else {
+                CsvLayout::List
+            };
+            let options = if italian {
+                CsvOptions::italian()
+            } else {
+                CsvOptions::default()
+            };
+            if let Err(e) = export_csv(&efforts, &path.to_string_lossy(), layout, &options) {
+                println!("Error during export the file {:?}: {}", path, e);
+            }
+        }
+    });
+}
@@ -0,0 +1,38 @@
+//! Callback handler for exporting the HTML report.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{AppWindow, PjmCallback, exporters::export_html, models::EffortsDto};
+
+/// Registers the export HTML callback.
+///
+/// This callback asks for a destination file and writes a self-contained HTML
+/// report of the current plan. Exporting is allowed in read-only mode too.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+pub fn register_on_export_html(ui: &AppWindow) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_export_html(move || {
+        println!("on_export_html");
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+
+        let path = rfd::FileDialog::new()
+            .add_filter("HTML files", &["html", "htm"])
+            .set_title("Esporta report HTML")
+            .set_file_name("efforts.html")
+            .save_file();
+
+        if let Some(path) = path {
+            let efforts = EffortsDto::from(ui.get_efforts());
+            let title = PjmCallback::get(&ui).get_current_file().to_string();
+            if let Err(e) = export_html(&efforts, &title, &path.to_string_lossy()) {
+                println!("Error during export the file {:?}: {}", path, e);
+            }
+        }
+    });
+}
@@ -0,0 +1,52 @@
+//! Callback handler for exporting the assignments as iCalendar files.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    exporters::{export_ics, export_ics_per_worker},
+    models::EffortsDto,
+};
+
+/// Registers the export ics callback.
+///
+/// This callback writes the assignments as all-day calendar events, either in a
+/// single combined `.ics` file or in one file per worker inside a chosen folder.
+/// Exporting is allowed in read-only mode too.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+pub fn register_on_export_ics(ui: &AppWindow) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_export_ics(move |per_worker: bool| {
+        println!("on_export_ics - per_worker: {}", per_worker);
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        let efforts = EffortsDto::from(ui.get_efforts());
+
+        if per_worker {
+            let dir = rfd::FileDialog::new()
+                .set_title("Cartella per i calendari dei worker")
+                .pick_folder();
+            if let Some(dir) = dir
+                && let Err(e) = export_ics_per_worker(&efforts, &dir.to_string_lossy())
+            {
+                println!("Error during export in {:?}: {}", dir, e);
+            }
+        } else {
+            let path = rfd::FileDialog::new()
+                .add_filter("iCalendar files", &["ics"])
+                .set_title("Esporta calendario")
+                .set_file_name("efforts.ics")
+                .save_file();
+            if let Some(path) = path
+                && let Err(e) = export_ics(&efforts, None, &path.to_string_lossy())
+            {
+                println!("Error during export the file {:?}: {}", path, e);
+            }
+        }
+    });
+}
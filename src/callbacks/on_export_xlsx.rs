@@ -0,0 +1,38 @@
+//! Callback handler for exporting the plan to an Excel workbook.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{AppWindow, PjmCallback, exporters::export_xlsx, models::EffortsDto};
+
+/// Registers the export xlsx callback.
+///
+/// This callback asks for a destination file and writes the current plan as an
+/// Excel workbook, with one coloured sheet per project and one for the
+/// over-allocation. Exporting is allowed in read-only mode too.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+pub fn register_on_export_xlsx(ui: &AppWindow) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_export_xlsx(move || {
+        println!("on_export_xlsx");
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+
+        let path = rfd::FileDialog::new()
+            .add_filter("Excel files", &["xlsx"])
+            .set_title("Esporta Excel")
+            .set_file_name("efforts.xlsx")
+            .save_file();
+
+        if let Some(path) = path {
+            let efforts = EffortsDto::from(ui.get_efforts());
+            if let Err(e) = export_xlsx(&efforts, &path.to_string_lossy()) {
+                println!("Error during export the file {:?}: {}", path, e);
+            }
+        }
+    });
+}
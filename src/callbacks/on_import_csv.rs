@@ -0,0 +1,71 @@
+//! Callback handler for importing assignments from CSV.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    callbacks::{PlanModels, populate_models},
+    importers::{ImportMode, import_csv_file},
+    models::EffortsDto,
+};
+
+/// Registers the import CSV callback.
+///
+/// This callback asks for a CSV of (project, team, worker, week, percent) lines,
+/// merges it into the current plan (or replaces the affected cells), reloads the
+/// models with the recalculated totals and shows the import report.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the import
+pub fn register_on_import_csv(ui: &AppWindow, models: PlanModels) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_import_csv(move |replace: bool| {
+        println!("on_import_csv - replace: {}", replace);
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        if PjmCallback::get(&ui).get_readonly() {
+            return;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("CSV files", &["csv", "txt"])
+            .set_title("Importa assegnazioni CSV")
+            .pick_file()
+        else {
+            return;
+        };
+
+        let mode = if replace {
+            ImportMode::Replace
+        } else {
+            ImportMode::Merge
+        };
+        let mut efforts = EffortsDto::from(ui.get_efforts());
+        let report = match import_csv_file(&mut efforts, &path.to_string_lossy(), mode, None) {
+            Ok(report) => report,
+            Err(e) => {
+                println!("Error during load the file {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        if report.imported > 0 {
+            populate_models(efforts, None, &models, &ui);
+            PjmCallback::get(&ui).set_changed(true);
+        }
+
+        rfd::MessageDialog::new()
+            .set_title("Import CSV")
+            .set_level(if report.errors.is_empty() {
+                rfd::MessageLevel::Info
+            } else {
+                rfd::MessageLevel::Warning
+            })
+            .set_description(report.to_string())
+            .show();
+    });
+}
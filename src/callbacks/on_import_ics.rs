@@ -0,0 +1,66 @@
+//! Callback handler for importing holidays from an iCalendar file.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    callbacks::{PlanModels, populate_models},
+    importers::import_ics_file,
+    models::EffortsDto,
+};
+
+/// Registers the import ICS callback.
+///
+/// This callback asks for an .ics calendar, adds its all-day events as holidays
+/// for everyone, reloads the models with the recalculated over-allocation and
+/// shows the import report.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the import
+pub fn register_on_import_ics(ui: &AppWindow, models: PlanModels) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_import_ics(move || {
+        println!("on_import_ics");
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        if PjmCallback::get(&ui).get_readonly() {
+            return;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("iCalendar files", &["ics"])
+            .set_title("Importa festività")
+            .pick_file()
+        else {
+            return;
+        };
+
+        let mut efforts = EffortsDto::from(ui.get_efforts());
+        let report = match import_ics_file(&mut efforts, &path.to_string_lossy(), &[]) {
+            Ok(report) => report,
+            Err(e) => {
+                println!("Error during load the file {:?}: {}", path, e);
+                return;
+            }
+        };
+
+        if report.imported > 0 {
+            populate_models(efforts, None, &models, &ui);
+            PjmCallback::get(&ui).set_changed(true);
+        }
+
+        rfd::MessageDialog::new()
+            .set_title("Import holidays")
+            .set_level(if report.skipped.is_empty() {
+                rfd::MessageLevel::Info
+            } else {
+                rfd::MessageLevel::Warning
+            })
+            .set_description(report.to_string())
+            .show();
+    });
+}
@@ -0,0 +1,82 @@
+//! Callback handler for importing assignments from an MS Project XML file.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    callbacks::{PlanModels, populate_models},
+    importers::{ImportMode, import_msproject_file, mapping_for},
+    models::EffortsDto,
+};
+
+/// Registers the import MS Project callback.
+///
+/// This callback asks for an MS Project XML export, maps its tasks and
+/// resources with the `<name>.mapping.json` file next to it (if any), merges
+/// the weekly allocations into the current plan (or replaces the affected
+/// cells), reloads the models and shows what was imported and what was not.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the import
+pub fn register_on_import_msproject(ui: &AppWindow, models: PlanModels) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_import_msproject(move |replace: bool| {
+        println!("on_import_msproject - replace: {}", replace);
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        if PjmCallback::get(&ui).get_readonly() {
+            return;
+        }
+
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("MS Project XML files", &["xml"])
+            .set_title("Importa da MS Project")
+            .pick_file()
+        else {
+            return;
+        };
+        let path = path.to_string_lossy().to_string();
+
+        let mode = if replace {
+            ImportMode::Replace
+        } else {
+            ImportMode::Merge
+        };
+        let mut efforts = EffortsDto::from(ui.get_efforts());
+        let report = match mapping_for(&path)
+            .and_then(|mapping| import_msproject_file(&mut efforts, &path, &mapping, mode))
+        {
+            Ok(report) => report,
+            Err(e) => {
+                println!("Error during load the file {:?}: {}", path, e);
+                rfd::MessageDialog::new()
+                    .set_title("Import MS Project")
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_description(e)
+                    .show();
+                return;
+            }
+        };
+
+        if report.assignments.imported > 0 {
+            populate_models(efforts, None, &models, &ui);
+            PjmCallback::get(&ui).set_changed(true);
+        }
+
+        rfd::MessageDialog::new()
+            .set_title("Import MS Project")
+            .set_level(
+                if report.assignments.errors.is_empty() && report.unmapped.is_empty() {
+                    rfd::MessageLevel::Info
+                } else {
+                    rfd::MessageLevel::Warning
+                },
+            )
+            .set_description(report.to_string())
+            .show();
+    });
+}
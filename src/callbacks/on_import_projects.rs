@@ -0,0 +1,131 @@
+//! Callback handlers for importing projects from another effort file.
+
+use slint::{ComponentHandle, Global, Model, ModelRc, VecModel};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::{
+    AppWindow, ImportChoice, PjmCallback,
+    callbacks::{PlanModels, populate_models},
+    file_io::load_efforts_from_file,
+    importers::{ProjectSelection, import_projects},
+    models::{Devs, EffortsDto},
+};
+
+/// Lists each project of `other` followed by its teams with a budget or
+/// assignments.
+fn choices(other: &EffortsDto) -> Vec<ImportChoice> {
+    let mut ret = vec![];
+    for prj in other.projects.iter() {
+        ret.push(ImportChoice {
+            project: prj.project,
+            dev: -1,
+            text: prj.text.clone().into(),
+            selected: false,
+        });
+        for team in prj.efforts.iter() {
+            if !team.is_empty() {
+                ret.push(ImportChoice {
+                    project: prj.project,
+                    dev: team.dev.into(),
+                    text: format!("{} ({}h)", team.dev.title(), team.effort).into(),
+                    selected: false,
+                });
+            }
+        }
+    }
+    ret
+}
+
+/// A project is imported when it, or at least one of its teams, is selected.
+/// A selected project brings all its teams.
+fn selection(choices: &[ImportChoice]) -> Vec<ProjectSelection> {
+    let mut ret = vec![];
+    for prj in choices.iter().filter(|c| c.dev < 0) {
+        let teams: Vec<Devs> = choices
+            .iter()
+            .filter(|c| c.project == prj.project && c.dev >= 0 && c.selected)
+            .map(|c| Devs::from(c.dev))
+            .collect();
+        if prj.selected || !teams.is_empty() {
+            ret.push(ProjectSelection {
+                project: prj.project,
+                teams: if prj.selected { vec![] } else { teams },
+            });
+        }
+    }
+    ret
+}
+
+/// Registers the import projects callbacks.
+///
+/// `import_projects` asks for another effort file and shows its projects and
+/// teams; `import_projects_closed` copies the selected ones into the plan,
+/// reloads the models and shows the import report.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the import
+pub fn register_on_import_projects(ui: &AppWindow, models: PlanModels) {
+    let other: Rc<RefCell<Option<EffortsDto>>> = Rc::new(RefCell::new(None));
+
+    {
+        let ui_weak = ui.as_weak();
+        let other = other.clone();
+        PjmCallback::get(ui).on_import_projects(move || {
+            println!("on_import_projects");
+
+            let Some(ui) = ui_weak.upgrade() else {
+                return;
+            };
+            if PjmCallback::get(&ui).get_readonly() {
+                return;
+            }
+
+            let Some(path) = rfd::FileDialog::new()
+                .add_filter("JSON files", &["json"])
+                .set_title("Importa progetti")
+                .pick_file()
+            else {
+                return;
+            };
+
+            let plan = load_efforts_from_file(&path.to_string_lossy());
+            PjmCallback::get(&ui).set_import_choices(ModelRc::new(VecModel::from(choices(&plan))));
+            *other.borrow_mut() = Some(plan);
+            PjmCallback::get(&ui).set_show_import_projects(true);
+        });
+    }
+
+    let ui_weak = ui.as_weak();
+    PjmCallback::get(ui).on_import_projects_closed(move |confirmed| {
+        println!("on_import_projects_closed - confirmed: {}", confirmed);
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        let Some(plan) = other.borrow_mut().take() else {
+            return;
+        };
+        if !confirmed {
+            return;
+        }
+
+        let picked: Vec<ImportChoice> = PjmCallback::get(&ui).get_import_choices().iter().collect();
+        let selection = selection(&picked);
+        if selection.is_empty() {
+            return;
+        }
+
+        let mut efforts = EffortsDto::from(ui.get_efforts());
+        let report = import_projects(&mut efforts, &plan, &selection);
+        populate_models(efforts, None, &models, &ui);
+        PjmCallback::get(&ui).set_changed(true);
+
+        rfd::MessageDialog::new()
+            .set_title("Import projects")
+            .set_level(rfd::MessageLevel::Info)
+            .set_description(report.to_string())
+            .show();
+    });
+}
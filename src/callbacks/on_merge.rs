@@ -0,0 +1,137 @@
+//! Callback handler for the three-way merge of two copies of the plan.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    callbacks::{PlanModels, populate_models},
+    file_io::read_efforts_file,
+    merge::{Resolution, merge, resolve_conflicts},
+    models::EffortsDto,
+};
+
+/// Asks with a dialog which side wins a conflict. `None` aborts the merge.
+fn ask_resolution(conflict: &crate::merge::Conflict) -> Option<Resolution> {
+    let result = rfd::MessageDialog::new()
+        .set_title("Conflitto nell'unione")
+        .set_level(rfd::MessageLevel::Warning)
+        .set_description(conflict.to_string())
+        .set_buttons(rfd::MessageButtons::YesNoCancelCustom(
+            "Nostra".to_string(),
+            "Loro".to_string(),
+            "Interrompi".to_string(),
+        ))
+        .show();
+    match result {
+        rfd::MessageDialogResult::Custom(text) if text == "Nostra" => Some(Resolution::Ours),
+        rfd::MessageDialogResult::Custom(text) if text == "Loro" => Some(Resolution::Theirs),
+        _ => None,
+    }
+}
+
+/// Registers the merge callback.
+///
+/// The plan shown in the window is "ours": this callback asks for the common
+/// ancestor and for their copy, merges them cell by cell, lets the user choose
+/// the conflicting values and reloads the models with the recalculated plan.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the merge
+pub fn register_on_merge(ui: &AppWindow, models: PlanModels) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_merge(move || {
+        println!("on_merge");
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        if PjmCallback::get(&ui).get_readonly() {
+            return;
+        }
+
+        let Some(base) = rfd::FileDialog::new()
+            .add_filter("File JSON", &["json"])
+            .set_title("Versione di partenza (base)")
+            .pick_file()
+        else {
+            return;
+        };
+        let Some(theirs) = rfd::FileDialog::new()
+            .add_filter("File JSON", &["json"])
+            .set_title("Versione da unire (theirs)")
+            .pick_file()
+        else {
+            return;
+        };
+
+        let ours = EffortsDto::from(ui.get_efforts());
+        let read = |path: std::path::PathBuf| {
+            let path = path.to_string_lossy().to_string();
+            read_efforts_file(&path).map_err(|e| format!("\"{}\": {}", path, e))
+        };
+        let merged = read(base).and_then(|base| {
+            let theirs = read(theirs)?;
+            merge(&base, &ours, &theirs)
+        });
+        let mut result = match merged {
+            Ok(result) => result,
+            Err(e) => {
+                rfd::MessageDialog::new()
+                    .set_title("Unione")
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_description(e)
+                    .show();
+                return;
+            }
+        };
+
+        if !result.conflicts.is_empty() {
+            let strategy = rfd::MessageDialog::new()
+                .set_title("Unione")
+                .set_level(rfd::MessageLevel::Warning)
+                .set_description(format!(
+                    "{} valori presi dalla loro versione, {} conflitti.",
+                    result.from_theirs,
+                    result.conflicts.len()
+                ))
+                .set_buttons(rfd::MessageButtons::YesNoCancelCustom(
+                    "Tieni la nostra".to_string(),
+                    "Prendi la loro".to_string(),
+                    "Scegli uno per uno…".to_string(),
+                ))
+                .show();
+            let strategy = match strategy {
+                rfd::MessageDialogResult::Custom(text) if text == "Tieni la nostra" => {
+                    Some(Resolution::Ours)
+                }
+                rfd::MessageDialogResult::Custom(text) if text == "Prendi la loro" => {
+                    Some(Resolution::Theirs)
+                }
+                rfd::MessageDialogResult::Custom(text) if text == "Scegli uno per uno…" => None,
+                _ => return,
+            };
+
+            let mut choices = vec![];
+            for conflict in result.conflicts.iter() {
+                match strategy.or_else(|| ask_resolution(conflict)) {
+                    Some(choice) => choices.push(choice),
+                    None => return,
+                }
+            }
+            resolve_conflicts(&mut result.merged, &result.conflicts, &choices);
+        } else {
+            rfd::MessageDialog::new()
+                .set_title("Unione")
+                .set_description(format!(
+                    "{} valori presi dalla loro versione, nessun conflitto.",
+                    result.from_theirs
+                ))
+                .show();
+        }
+
+        populate_models(result.merged, None, &models, &ui);
+        PjmCallback::get(&ui).set_changed(true);
+    });
+}
@@ -5,9 +5,9 @@ use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 use crate::{
-    AppWindow, EffortByPrjData, PjmCallback, SovraData,
-    callbacks::rebuild_project,
-    models::{DevId, ProjectId, SovraDto, effort_by_date::EffortByDateDataExt},
+    AppWindow, EffortByPrjData, HolidayData, PjmCallback, SovraData,
+    callbacks::{rebuild_project, update_sovra_week},
+    models::{CalendarDto, DevId, ProjectId},
 };
 
 /// Registers the move_effort callback.
@@ -20,6 +20,8 @@ pub fn register_on_move_effort(
     vec_model_projects: Rc<VecModel<EffortByPrjData>>,
     vec_model_sovra: Rc<VecModel<SovraData>>,
     vec_model_worker_names: Rc<VecModel<SharedString>>,
+    vec_model_week_off: Rc<VecModel<i32>>,
+    vec_model_holidays: Rc<VecModel<HolidayData>>,
 ) {
     let ui_weak = ui.as_weak();
 
@@ -135,34 +137,20 @@ pub fn register_on_move_effort(
                 .flat_map(|&w| [w, w + offset_weeks * 7])
                 .collect();
 
+            let calendar = ui_weak
+                .upgrade()
+                .map(|ui| CalendarDto::from(ui.get_efforts().calendar))
+                .unwrap_or_default();
             for aw in &affected {
-                let mut sovra_hash: HashMap<String, i32> = HashMap::new();
-                for p in 0..vec_model_projects.row_count() {
-                    let prj = vec_model_projects.row_data(p).unwrap();
-                    for d in 0..prj.efforts.row_count() {
-                        let dr = prj.efforts.row_data(d).unwrap();
-                        for di in 0..dr.datas.row_count() {
-                            let date = dr.datas.row_data(di).unwrap();
-                            if date.week == *aw {
-                                date.get_sovra(&mut sovra_hash);
-                                break;
-                            }
-                        }
-                    }
-                }
-                for s in 0..vec_model_sovra.row_count() {
-                    let mut sovra: SovraDto = vec_model_sovra.row_data(s).unwrap().into();
-                    if sovra.week == *aw {
-                        for n in 0..vec_model_worker_names.row_count() {
-                            let name = vec_model_worker_names.row_data(n).unwrap().to_string();
-                            if let Some(v) = sovra.value.get_mut(n) {
-                                *v = *sovra_hash.get(&name).unwrap_or(&0);
-                            }
-                        }
-                        vec_model_sovra.set_row_data(s, sovra.into());
-                        break;
-                    }
-                }
+                update_sovra_week(
+                    &vec_model_projects,
+                    &vec_model_worker_names,
+                    &vec_model_sovra,
+                    &vec_model_week_off,
+                    &vec_model_holidays,
+                    &calendar,
+                    *aw,
+                );
             }
 
             // --- Clear selection, mark changed ---
@@ -0,0 +1,74 @@
+//! Callback handler for renaming or merging a worker.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    callbacks::{PlanModels, populate_models},
+    models::EffortsDto,
+    workers::rename_worker,
+};
+
+/// Registers the rename worker callback.
+///
+/// This callback renames a worker in every cell, in the worker list and in the
+/// holidays. When the new name is already a worker, it asks whether to merge
+/// the two; the models are then reloaded, so totals and over-allocation are
+/// recomputed.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the rename
+pub fn register_on_rename_worker(ui: &AppWindow, models: PlanModels) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_rename_worker(move |from, to| {
+        println!("on_rename_worker {:?} -> {:?}", from, to);
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        if PjmCallback::get(&ui).get_readonly() {
+            return;
+        }
+
+        let mut efforts = EffortsDto::from(ui.get_efforts());
+        let to = to.trim();
+        if efforts.worker_names.iter().any(|n| n == to)
+            && rfd::MessageDialog::new()
+                .set_title("Merge workers")
+                .set_level(rfd::MessageLevel::Warning)
+                .set_description(format!(
+                    "\"{}\" is already a worker.\n\nMerge \"{}\" into \"{}\"?",
+                    to, from, to
+                ))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show()
+                != rfd::MessageDialogResult::Yes
+        {
+            return;
+        }
+
+        let report = match rename_worker(&mut efforts, &from, to) {
+            Ok(report) => report,
+            Err(e) => {
+                rfd::MessageDialog::new()
+                    .set_title("Rename worker")
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_description(e)
+                    .show();
+                return;
+            }
+        };
+
+        populate_models(efforts, None, &models, &ui);
+        PjmCallback::get(&ui).set_changed(true);
+        PjmCallback::get(&ui).set_show_rename_worker(false);
+
+        rfd::MessageDialog::new()
+            .set_title("Rename worker")
+            .set_level(rfd::MessageLevel::Info)
+            .set_description(report.to_string())
+            .show();
+    });
+}
@@ -0,0 +1,220 @@
+//! Callback handlers for the worker roster window.
+
+use chrono::NaiveDate;
+use slint::{ComponentHandle, Global, ModelRc, VecModel};
+
+use crate::{
+    AppWindow, PjmCallback, RosterData,
+    callbacks::{PlanModels, populate_models},
+    date_utils::{date_text, local_to_days, primo_giorno_settimana_corrente},
+    models::{Devs, EffortsDto, LoadTargetDto},
+    workers::{
+        add_worker, group_by_team, move_worker, purge_unused, retire_worker, roster, set_load,
+        set_team,
+    },
+};
+
+/// Fills the roster window from the plan shown in the UI.
+fn refresh(ui: &AppWindow) {
+    let efforts = EffortsDto::from(ui.get_efforts());
+    PjmCallback::get(ui).set_roster_default_load(efforts.load.to_string().into());
+    let entries: Vec<RosterData> = roster(&efforts)
+        .into_iter()
+        .map(|entry| RosterData {
+            name: entry.name.into(),
+            team: entry.team.map_or(-1, i32::from),
+            team_set: entry.team_set,
+            retired_from: entry.retired_from.map(date_text).unwrap_or_default().into(),
+            load: if entry.load_set {
+                entry.load.to_string().into()
+            } else {
+                Default::default()
+            },
+            assignments: entry.assignments as i32,
+        })
+        .collect();
+    PjmCallback::get(ui).set_roster_entries(ModelRc::new(VecModel::from(entries)));
+}
+
+fn show_error(text: String) {
+    rfd::MessageDialog::new()
+        .set_title("Worker roster")
+        .set_level(rfd::MessageLevel::Error)
+        .set_description(text)
+        .show();
+}
+
+/// Applies a roster change to the plan shown in the UI, then reloads the
+/// models and the roster so that footer and over-allocation stay consistent.
+///
+/// `action` returns whether the plan changed.
+fn apply(
+    ui: &AppWindow,
+    models: &PlanModels,
+    action: impl FnOnce(&mut EffortsDto) -> Result<bool, String>,
+) {
+    if PjmCallback::get(ui).get_readonly() {
+        return;
+    }
+
+    let mut efforts = EffortsDto::from(ui.get_efforts());
+    match action(&mut efforts) {
+        Ok(true) => {
+            populate_models(efforts, None, models, ui);
+            PjmCallback::get(ui).set_changed(true);
+            refresh(ui);
+        }
+        Ok(false) => refresh(ui),
+        Err(e) => show_error(e),
+    }
+}
+
+/// Parses a YYYY-MM-DD date into the Monday of its week; empty is `None`.
+fn parse_week(text: &str) -> Result<Option<i32>, String> {
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d")
+        .map(|date| Some(local_to_days(&primo_giorno_settimana_corrente(&date))))
+        .map_err(|_| format!("Data non valida: \"{}\" (formato YYYY-MM-DD)", text))
+}
+
+/// Registers the roster callbacks.
+///
+/// `roster` opens the window listing the workers in footer order. The other
+/// callbacks add a worker, move one up or down, set their team, retire them
+/// from a week on (they are left out of the footer from then), set the load
+/// their footer is coloured against, order the footer by team or remove the
+/// workers without assignments.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after each change
+pub fn register_on_roster(ui: &AppWindow, models: PlanModels) {
+    let pjm = PjmCallback::get(ui);
+
+    let ui_weak = ui.as_weak();
+    pjm.on_roster(move || {
+        println!("on_roster");
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        refresh(&ui);
+        PjmCallback::get(&ui).set_show_roster(true);
+    });
+
+    let ui_weak = ui.as_weak();
+    let plan = models.clone();
+    pjm.on_roster_add(move |name| {
+        println!("on_roster_add {:?}", name);
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        apply(&ui, &plan, |efforts| {
+            add_worker(efforts, &name).map(|_| true)
+        });
+    });
+
+    let ui_weak = ui.as_weak();
+    let plan = models.clone();
+    pjm.on_roster_move(move |name, delta| {
+        println!("on_roster_move {:?} {}", name, delta);
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        apply(&ui, &plan, |efforts| {
+            move_worker(efforts, &name, delta);
+            Ok(true)
+        });
+    });
+
+    let ui_weak = ui.as_weak();
+    let plan = models.clone();
+    pjm.on_roster_set_team(move |name, team| {
+        println!("on_roster_set_team {:?} {}", name, team);
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        apply(&ui, &plan, |efforts| {
+            set_team(efforts, &name, (team >= 0).then(|| Devs::from(team)));
+            Ok(true)
+        });
+    });
+
+    let ui_weak = ui.as_weak();
+    let plan = models.clone();
+    pjm.on_roster_retire(move |name, date| {
+        println!("on_roster_retire {:?} {:?}", name, date);
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        apply(&ui, &plan, |efforts| {
+            retire_worker(efforts, &name, parse_week(&date)?);
+            Ok(true)
+        });
+    });
+
+    let ui_weak = ui.as_weak();
+    let plan = models.clone();
+    pjm.on_roster_set_load(move |name, load| {
+        println!("on_roster_set_load {:?} {:?}", name, load);
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        apply(&ui, &plan, |efforts| {
+            let load = if load.trim().is_empty() {
+                None
+            } else {
+                Some(LoadTargetDto::parse(&load)?)
+            };
+            set_load(efforts, Some(name.as_str()).filter(|n| !n.is_empty()), load);
+            Ok(true)
+        });
+    });
+
+    let ui_weak = ui.as_weak();
+    let plan = models.clone();
+    pjm.on_roster_group_by_team(move || {
+        println!("on_roster_group_by_team");
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        apply(&ui, &plan, |efforts| {
+            group_by_team(efforts);
+            Ok(true)
+        });
+    });
+
+    let ui_weak = ui.as_weak();
+    pjm.on_roster_purge(move || {
+        println!("on_roster_purge");
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        apply(&ui, &models, |efforts| {
+            let unused: Vec<String> = roster(efforts)
+                .into_iter()
+                .filter(|entry| entry.assignments == 0)
+                .map(|entry| entry.name)
+                .collect();
+            if unused.is_empty() {
+                return Err("Nessun worker senza assegnazioni".to_string());
+            }
+            let confirmed = rfd::MessageDialog::new()
+                .set_title("Remove unused workers")
+                .set_level(rfd::MessageLevel::Warning)
+                .set_description(format!(
+                    "Remove the workers without assignments?\n\n{}",
+                    unused.join("\n")
+                ))
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show()
+                == rfd::MessageDialogResult::Yes;
+            if !confirmed {
+                return Ok(false);
+            }
+            purge_unused(efforts);
+            Ok(true)
+        });
+    });
+}
@@ -6,18 +6,24 @@ use std::rc::Rc;
 
 use crate::{
     file_io::save_efforts_to_file,
-    models::{EffortByPrjDto, EffortsDto, SovraDto},
-    AppWindow, EffortByPrjData, PjmCallback, SovraData,
+    models::{
+        CalendarDto, EffortByPrjDto, EffortsDto, HolidayDto, LoadTargetDto, SovraDto, WorkerDto,
+    },
+    AppWindow, EffortByPrjData, HolidayData, PjmCallback, SovraData,
 };
 
 /// Registers the save file callback.
 ///
 /// This callback collects all data from the UI models and saves it to a JSON file.
+/// Nothing is written while the application is in read-only mode. If saving
+/// fails, e.g. someone else changed the same rows of a shared database, the
+/// error is shown and the plan stays marked as changed.
 ///
 /// # Arguments
 /// * `ui` - Reference to the main application window
 /// * `vec_model_projects` - Project data model
 /// * `vec_model_week_off` - Week off data model
+/// * `vec_model_holidays` - Holidays model
 /// * `vec_model_worker_names` - Worker names model
 /// * `vec_model_sovra` - Over-allocation tracking model
 /// * `current_file` - Shared reference to the current file path
@@ -25,6 +31,7 @@ pub fn register_on_save_file(
     ui: &AppWindow,
     vec_model_projects: Rc<VecModel<EffortByPrjData>>,
     vec_model_week_off: Rc<VecModel<i32>>,
+    vec_model_holidays: Rc<VecModel<HolidayData>>,
     vec_model_worker_names: Rc<VecModel<SharedString>>,
     vec_model_sovra: Rc<VecModel<SovraData>>,
     current_file: Rc<RefCell<String>>,
@@ -32,6 +39,14 @@ pub fn register_on_save_file(
     let ui_weak = ui.as_weak();
 
     PjmCallback::get(ui).on_save_file(move || {
+        if ui_weak
+            .upgrade()
+            .is_some_and(|ui| PjmCallback::get(&ui).get_readonly())
+        {
+            println!("on_save_file - read-only mode, save skipped");
+            return;
+        }
+
         // Usa iteratori invece di loop manuali - più efficiente
         let projects: Vec<EffortByPrjDto> = (0..vec_model_projects.row_count())
             .filter_map(|i| vec_model_projects.row_data(i))
@@ -42,6 +57,11 @@ pub fn register_on_save_file(
             .filter_map(|i| vec_model_week_off.row_data(i))
             .collect();
 
+        let holidays: Vec<HolidayDto> = (0..vec_model_holidays.row_count())
+            .filter_map(|i| vec_model_holidays.row_data(i))
+            .map(HolidayDto::from)
+            .collect();
+
         let worker_names: Vec<SharedString> = (0..vec_model_worker_names.row_count())
             .filter_map(|i| vec_model_worker_names.row_data(i))
             .collect();
@@ -51,14 +71,39 @@ pub fn register_on_save_file(
             .map(SovraDto::from)
             .collect();
 
+        let (calendar, load, workers) = ui_weak
+            .upgrade()
+            .map(|ui| {
+                let efforts = ui.get_efforts();
+                (
+                    CalendarDto::from(efforts.calendar),
+                    LoadTargetDto::from(efforts.load),
+                    efforts.workers.iter().map(WorkerDto::from).collect(),
+                )
+            })
+            .unwrap_or_default();
+
         // Ottimizzazione: converti direttamente in EffortsDto senza passaggio intermedio
         let dto = EffortsDto {
             sovra,
             week_off,
+            holidays,
+            calendar,
+            load,
             worker_names: worker_names.iter().map(|s| s.to_string()).collect(),
+            workers,
             projects,
         };
-        let _ = save_efforts_to_file(&dto, &current_file.borrow());
+        if let Err(e) = save_efforts_to_file(&dto, &current_file.borrow()) {
+            // Left as changed, e.g. after a conflict on a shared database
+            println!("Error during saving the file: {}", e);
+            rfd::MessageDialog::new()
+                .set_title("Save failed")
+                .set_level(rfd::MessageLevel::Error)
+                .set_description(e.to_string())
+                .show();
+            return;
+        }
         if let Some(ui) = ui_weak.upgrade() {
             PjmCallback::get(&ui).set_changed(false);
         }
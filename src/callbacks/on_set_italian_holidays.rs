@@ -0,0 +1,38 @@
+//! Callback handler for enabling the Italian public holidays.
+
+use slint::{ComponentHandle, Global};
+
+use crate::{
+    AppWindow, PjmCallback,
+    callbacks::{PlanModels, populate_models},
+    models::EffortsDto,
+};
+
+/// Registers the set Italian holidays callback.
+///
+/// This callback turns the Italian public holidays on or off for the plan, then
+/// reloads the models so that over-allocation and header marks are recalculated.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the change
+pub fn register_on_set_italian_holidays(ui: &AppWindow, models: PlanModels) {
+    let ui_weak = ui.as_weak();
+
+    PjmCallback::get(ui).on_set_italian_holidays(move |enabled: bool| {
+        println!("on_set_italian_holidays - enabled: {}", enabled);
+
+        let Some(ui) = ui_weak.upgrade() else {
+            return;
+        };
+        if PjmCallback::get(&ui).get_readonly() {
+            return;
+        }
+
+        let mut efforts = EffortsDto::from(ui.get_efforts());
+        efforts.calendar.italian_holidays = enabled;
+        efforts.rebuild();
+        populate_models(efforts, None, &models, &ui);
+        PjmCallback::get(&ui).set_changed(true);
+    });
+}
@@ -0,0 +1,102 @@
+//! Callback handlers for the plan validation window.
+
+use slint::{ComponentHandle, Global, Model, ModelRc, VecModel};
+
+use crate::{
+    AppWindow, PjmCallback, ProblemData,
+    callbacks::{PlanModels, populate_models},
+    models::EffortsDto,
+    validate::{fix, validate},
+};
+
+/// Validates the plan shown in the UI and fills the problem list.
+fn refresh(ui: &AppWindow) {
+    let efforts = EffortsDto::from(ui.get_efforts());
+    let report = validate(&efforts);
+    println!("{}", report);
+
+    let first_week = ui.get_weeks().row_data(0).map(|d| d.week);
+    let problems: Vec<ProblemData> = report
+        .problems
+        .iter()
+        .map(|p| ProblemData {
+            text: p.to_string().into(),
+            project: p.project.map_or(-1, |n| n as i32),
+            dev: p.dev.map_or(-1, i32::from),
+            week_index: match (p.week, first_week) {
+                (Some(week), Some(first)) if week >= first => (week - first) / 7,
+                _ => -1,
+            },
+            fixable: p.fixable,
+        })
+        .collect();
+    PjmCallback::get(ui).set_problems(ModelRc::new(VecModel::from(problems)));
+}
+
+/// Registers the validation callbacks.
+///
+/// `validate` checks the plan and opens the problem list; `fix_problems`
+/// applies the safe fixes, reloads the models and validates again;
+/// `goto_problem` shows only the project (and team) of the selected problem,
+/// like a search. Ctrl+Shift+F shows everything again.
+///
+/// # Arguments
+/// * `ui` - Reference to the main application window
+/// * `models` - The UI models reloaded after the fixes
+pub fn register_on_validate(ui: &AppWindow, models: PlanModels) {
+    {
+        let ui_weak = ui.as_weak();
+        PjmCallback::get(ui).on_validate(move || {
+            println!("on_validate");
+
+            let Some(ui) = ui_weak.upgrade() else {
+                return;
+            };
+            refresh(&ui);
+            PjmCallback::get(&ui).set_show_problems(true);
+        });
+    }
+
+    {
+        let ui_weak = ui.as_weak();
+        let models = models.clone();
+        PjmCallback::get(ui).on_fix_problems(move || {
+            println!("on_fix_problems");
+
+            let Some(ui) = ui_weak.upgrade() else {
+                return;
+            };
+            if PjmCallback::get(&ui).get_readonly() {
+                return;
+            }
+
+            let mut efforts = EffortsDto::from(ui.get_efforts());
+            let fixed = fix(&mut efforts);
+            if fixed > 0 {
+                populate_models(efforts, None, &models, &ui);
+                PjmCallback::get(&ui).set_changed(true);
+            }
+            refresh(&ui);
+        });
+    }
+
+    PjmCallback::get(ui).on_goto_problem(move |problem: ProblemData| {
+        println!("on_goto_problem {:?}", problem);
+
+        if problem.project < 0 {
+            return;
+        }
+        let projects = &models.projects;
+        for project_index in 0..projects.row_count() {
+            let mut project = projects.row_data(project_index).unwrap_or_default();
+            project.visible = project_index as i32 == problem.project;
+
+            for effort_index in 0..project.efforts.row_count() {
+                let mut dev = project.efforts.row_data(effort_index).unwrap_or_default();
+                dev.visible = problem.dev < 0 || dev.dev == problem.dev;
+                project.efforts.set_row_data(effort_index, dev);
+            }
+            projects.set_row_data(project_index, project);
+        }
+    });
+}
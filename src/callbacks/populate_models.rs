@@ -0,0 +1,157 @@
+//! Loading of an [`EffortsDto`] into the UI models.
+
+use chrono::NaiveDate;
+use slint::{Global, Model, ModelRc, SharedString, VecModel};
+use std::rc::Rc;
+
+use crate::{
+    AppWindow, DayData, EffortByPrjData, HolidayData, PjmCallback, SovraData, WorkerData,
+    date_utils::{days_to_local, local_to_days, primo_giorno_settimana_corrente, weeks_list},
+    models::{EffortsDto, holiday::working_days},
+};
+
+/// The UI models holding a plan, shared by the callbacks that reload it.
+#[derive(Clone)]
+pub struct PlanModels {
+    pub projects: Rc<VecModel<EffortByPrjData>>,
+    pub week_off: Rc<VecModel<i32>>,
+    pub holidays: Rc<VecModel<HolidayData>>,
+    pub worker_names: Rc<VecModel<SharedString>>,
+    pub sovra: Rc<VecModel<SovraData>>,
+}
+
+/// Replaces the content of the UI models with the given efforts.
+///
+/// The week range is aligned across all projects (optionally starting from
+/// `start_date_override`) and the `sovra` vector is synchronized to it. Weeks
+/// with holidays for everyone are marked in the header.
+///
+/// # Arguments
+/// * `app_info` - The efforts to show
+/// * `start_date_override` - Optional first week to display
+/// * `models` - The UI models to fill
+/// * `ui` - Reference to the main application window
+pub fn populate_models(
+    mut app_info: EffortsDto,
+    start_date_override: Option<NaiveDate>,
+    models: &PlanModels,
+    ui: &AppWindow,
+) {
+    let PlanModels {
+        projects: vec_model_projects,
+        week_off: vec_model_week_off,
+        holidays: vec_model_holidays,
+        worker_names: vec_model_worker_names,
+        sovra: vec_model_sovra,
+    } = models;
+
+    // Clear existing data
+    for i in (0..vec_model_projects.row_count()).rev() {
+        vec_model_projects.remove(i);
+    }
+    for i in (0..vec_model_week_off.row_count()).rev() {
+        vec_model_week_off.remove(i);
+    }
+    for i in (0..vec_model_holidays.row_count()).rev() {
+        vec_model_holidays.remove(i);
+    }
+    for i in (0..vec_model_worker_names.row_count()).rev() {
+        vec_model_worker_names.remove(i);
+    }
+    for i in (0..vec_model_sovra.row_count()).rev() {
+        vec_model_sovra.remove(i);
+    }
+
+    let (mut start_week, end_week) = app_info.start_end_weeks();
+
+    if let Some(date) = start_date_override {
+        start_week = local_to_days(&primo_giorno_settimana_corrente(&date));
+    }
+
+    println!("start_week: {} - end_week: {}", start_week, end_week);
+
+    app_info.sync_sovra(start_week, end_week);
+
+    // Retired workers are left out of the footer from their week on, and the
+    // whole row when that is before the first week
+    let retired_from: Vec<i32> = app_info
+        .worker_names
+        .iter()
+        .map(|name| {
+            app_info
+                .worker(name)
+                .and_then(|w| w.retired_from)
+                .unwrap_or(-1)
+        })
+        .collect();
+    let retired: Vec<bool> = app_info
+        .worker_names
+        .iter()
+        .map(|name| app_info.worker(name).is_some_and(|w| w.is_retired(start_week)))
+        .collect();
+    PjmCallback::get(ui).set_retired_from(ModelRc::new(VecModel::from(retired_from)));
+    PjmCallback::get(ui).set_retired_count(retired.iter().filter(|r| **r).count() as i32);
+    PjmCallback::get(ui).set_retired_workers(ModelRc::new(VecModel::from(retired)));
+
+    // Each worker's footer is coloured against their own intended load
+    let load_targets: Vec<crate::LoadTargetData> = app_info
+        .worker_names
+        .iter()
+        .map(|name| app_info.load_target(name).into())
+        .collect();
+    PjmCallback::get(ui).set_load_targets(ModelRc::new(VecModel::from(load_targets)));
+
+    let mut weeks_day_dto = weeks_list(&days_to_local(start_week), &days_to_local(end_week));
+    for day in weeks_day_dto.iter_mut() {
+        day.days_off = 5 - working_days(
+            &app_info.holidays,
+            &app_info.week_off,
+            &app_info.calendar,
+            day.week,
+            "",
+        );
+    }
+    let weeks_day_data = ModelRc::new(VecModel::from(
+        weeks_day_dto
+            .into_iter()
+            .map(DayData::from)
+            .collect::<Vec<_>>(),
+    ));
+    ui.set_weeks(weeks_day_data);
+
+    for dto in app_info.projects.iter_mut() {
+        dto.set_date(start_week, end_week);
+    }
+
+    for dto in app_info.projects.into_iter() {
+        vec_model_projects.push(dto.into());
+    }
+
+    for week in app_info.week_off {
+        vec_model_week_off.push(week);
+    }
+
+    for holiday in app_info.holidays {
+        vec_model_holidays.push(holiday.into());
+    }
+
+    for person in app_info.worker_names {
+        vec_model_worker_names.push(person.into());
+    }
+
+    for sovra in app_info.sovra {
+        vec_model_sovra.push(sovra.into());
+    }
+
+    let mut efforts = ui.get_efforts();
+    efforts.calendar = app_info.calendar.into();
+    efforts.load = app_info.load.into();
+    efforts.workers = ModelRc::new(VecModel::from(
+        app_info
+            .workers
+            .into_iter()
+            .map(WorkerData::from)
+            .collect::<Vec<_>>(),
+    ));
+    ui.set_efforts(efforts);
+}
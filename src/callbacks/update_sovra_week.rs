@@ -0,0 +1,69 @@
+//! Over-allocation (sovra) recalculation for a single week.
+
+use slint::{Model, SharedString, VecModel};
+use std::collections::HashMap;
+
+use crate::{
+    EffortByPrjData, HolidayData, SovraData,
+    models::{
+        CalendarDto, HolidayDto, SovraDto,
+        effort_by_date::EffortByDateDataExt,
+        holiday::{effective_load, working_days},
+    },
+};
+
+/// Recalculates the `sovra` row of a week.
+///
+/// The percentages of each worker are summed over every project and team, then
+/// scaled to the working days left by holidays, public holidays and closure weeks.
+///
+/// # Arguments
+/// * `vec_model_projects` - Project data model
+/// * `vec_model_worker_names` - Worker names model
+/// * `vec_model_sovra` - Over-allocation tracking model
+/// * `vec_model_week_off` - Closure weeks model
+/// * `vec_model_holidays` - Holidays model
+/// * `calendar` - Public holidays settings
+/// * `week` - Monday of the week to recalculate
+pub fn update_sovra_week(
+    vec_model_projects: &VecModel<EffortByPrjData>,
+    vec_model_worker_names: &VecModel<SharedString>,
+    vec_model_sovra: &VecModel<SovraData>,
+    vec_model_week_off: &VecModel<i32>,
+    vec_model_holidays: &VecModel<HolidayData>,
+    calendar: &CalendarDto,
+    week: i32,
+) {
+    let mut sovra_hash: HashMap<String, i32> = HashMap::new();
+    for p in 0..vec_model_projects.row_count() {
+        let prj = vec_model_projects.row_data(p).unwrap();
+        for d in 0..prj.efforts.row_count() {
+            let dr = prj.efforts.row_data(d).unwrap();
+            for di in 0..dr.datas.row_count() {
+                let date = dr.datas.row_data(di).unwrap();
+                if date.week == week {
+                    date.get_sovra(&mut sovra_hash);
+                    break;
+                }
+            }
+        }
+    }
+
+    let week_off: Vec<i32> = vec_model_week_off.iter().collect();
+    let holidays: Vec<HolidayDto> = vec_model_holidays.iter().map(HolidayDto::from).collect();
+
+    for s in 0..vec_model_sovra.row_count() {
+        let mut sovra: SovraDto = vec_model_sovra.row_data(s).unwrap().into();
+        if sovra.week == week {
+            for n in 0..vec_model_worker_names.row_count() {
+                let name = vec_model_worker_names.row_data(n).unwrap().to_string();
+                if let Some(v) = sovra.value.get_mut(n) {
+                    let days = working_days(&holidays, &week_off, calendar, week, &name);
+                    *v = effective_load(*sovra_hash.get(&name).unwrap_or(&0), days);
+                }
+            }
+            vec_model_sovra.set_row_data(s, sovra.into());
+            break;
+        }
+    }
+}
@@ -0,0 +1,1375 @@
+//! Command line subcommands that run without opening the GUI.
+//!
+//! ```text
+//! project_app export-csv <input.json> <output.csv> [--matrix] [--separator <c>] [--decimal <c>] [--italian]
+//! project_app export-xlsx <input.json> <output.xlsx>
+//! project_app export-html <input.json> <output.html>
+//! project_app export-gantt <input.json> <output.mmd|puml> [--mermaid|--plantuml]
+//! project_app export-ics <input.json> <output.ics|dir> [--worker <name>] [--per-worker]
+//! project_app anonymize <input.json> <output.json> [--roles] [--mask-projects] [--mapping <map.json>]
+//! project_app anonymize <anonymized.json> <output.json> --reverse [--mapping <map.json>]
+//! project_app digest <input.json> [--week <YYYY-MM-DD>] [--weeks <N>] [--output <out.md>]
+//! project_app import-csv <plan.json> <assignments.csv> [--replace] [--separator <c>] [--output <out.json>]
+//! project_app import-msp <plan.json> <project.xml> [--mapping <map.json>] [--replace] [--output <out.json>]
+//! project_app import-projects <plan.json> <other.json> [--project <name>[:<team>,...]]... [--output <out.json>]
+//! project_app import-ics <plan.json> <holidays.ics> [--worker <name>]... [--output <out.json>]
+//! project_app holidays <plan.json> [--italian|--no-italian] [--patron <DD/MM>|--no-patron] [--output <out.json>]
+//! project_app merge <base.json> <ours.json> <theirs.json> [--ours|--theirs] --output <out.json>
+//! project_app diff <old.json> <new.json>
+//! project_app validate <plan.json> [--fix] [--output <out.json>]
+//! project_app rename-worker <plan.json> <old name> <new name> [--output <out.json>]
+//! project_app workers <plan.json> [--add <name>] [--retire <name>=<YYYY-MM-DD>] [--unretire <name>] [--team <name>=<team>] [--load <name>=<target>[/<max>]] [--default-load <target>[/<max>]] [--group-by-team] [--purge] [--output <out.json>]
+//! project_app schema [--compact] [--output <schema.json>]
+//! project_app convert <input.json|db> <output.json|db> [--compact|--pretty]
+//! ```
+
+use chrono::{Datelike, NaiveDate, Utc};
+
+use crate::date_utils::{date_text, days_to_local, local_to_days, primo_giorno_settimana_corrente};
+use crate::diff::diff;
+use crate::exporters::{
+    AnonymizeMapping, AnonymizeOptions, CsvLayout, CsvOptions, GanttSyntax, Pseudonym, deanonymize,
+    export_anonymized, export_csv, export_gantt, export_html, export_ics, export_ics_per_worker,
+    export_xlsx, render_digest,
+};
+use crate::file_io::{
+    FileLayout, is_sqlite_path, read_efforts_file, save_efforts_to_file, save_efforts_with_layout,
+};
+use crate::importers::{
+    ImportMode, ProjectSelection, import_csv_file, import_ics_file, import_msproject_file,
+    import_projects, load_mapping, mapping_for,
+};
+use crate::merge::{Resolution, merge, resolve_conflicts};
+use crate::models::{CalendarDto, Devs, EffortsDto, LoadTargetDto};
+use crate::schema::{compact_schema, efforts_schema, render_schema};
+use crate::validate::{fix, validate};
+use crate::workers::{
+    add_worker, group_by_team, purge_unused, rename_worker, retire_worker, roster, set_load,
+    set_team,
+};
+
+/// Runs a subcommand if the first argument names one.
+///
+/// # Arguments
+/// * `args` - Command line arguments, without the program name
+///
+/// # Returns
+/// * `Some(exit_code)` - If a subcommand was executed
+/// * `None` - If the arguments are meant for the GUI
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        "export-csv" => Some(cmd_export_csv(rest)),
+        "export-xlsx" => Some(cmd_export_xlsx(rest)),
+        "export-html" => Some(cmd_export_html(rest)),
+        "export-gantt" => Some(cmd_export_gantt(rest)),
+        "export-ics" => Some(cmd_export_ics(rest)),
+        "anonymize" => Some(cmd_anonymize(rest)),
+        "digest" => Some(cmd_digest(rest)),
+        "import-csv" => Some(cmd_import_csv(rest)),
+        "import-msp" => Some(cmd_import_msp(rest)),
+        "import-projects" => Some(cmd_import_projects(rest)),
+        "import-ics" => Some(cmd_import_ics(rest)),
+        "holidays" => Some(cmd_holidays(rest)),
+        "merge" => Some(cmd_merge(rest)),
+        "diff" => Some(cmd_diff(rest)),
+        "validate" => Some(cmd_validate(rest)),
+        "rename-worker" => Some(cmd_rename_worker(rest)),
+        "workers" => Some(cmd_workers(rest)),
+        "convert" => Some(cmd_convert(rest)),
+        "schema" => Some(cmd_schema(rest)),
+        _ => None,
+    }
+}
+
+fn load(path: &str) -> Option<EffortsDto> {
+    if !std::path::Path::new(path).exists() {
+        eprintln!("File non trovato: {}", path);
+        return None;
+    }
+    match read_efforts_file(path) {
+        Ok(efforts) => Some(efforts),
+        Err(e) => {
+            eprintln!("Errore durante la lettura di \"{}\": {}", path, e);
+            None
+        }
+    }
+}
+
+fn parse_char(value: &str) -> Option<u8> {
+    match value {
+        "tab" | "\\t" => Some(b'\t'),
+        v if v.len() == 1 && v.is_ascii() => Some(v.as_bytes()[0]),
+        _ => None,
+    }
+}
+
+fn cmd_export_csv(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app export-csv <input.json> <output.csv> [--matrix] [--separator <c>] [--decimal <c>] [--italian]";
+
+    let mut files = vec![];
+    let mut layout = CsvLayout::List;
+    let mut options = CsvOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--matrix" | "-m" => layout = CsvLayout::Matrix,
+            "--italian" => options = CsvOptions::italian(),
+            "--separator" | "-s" => {
+                i += 1;
+                match args.get(i).and_then(|v| parse_char(v)) {
+                    Some(c) => options.separator = c,
+                    None => {
+                        eprintln!("Separatore non valido\n{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--decimal" => {
+                i += 1;
+                match args.get(i).map(|v| v.as_str()) {
+                    Some(",") => options.decimal_comma = true,
+                    Some(".") => options.decimal_comma = false,
+                    _ => {
+                        eprintln!("Separatore decimale non valido (atteso ',' o '.')\n{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [input, output] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    match export_csv(&efforts, output, layout, &options) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_export_xlsx(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app export-xlsx <input.json> <output.xlsx>";
+
+    let [input, output] = args else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    match export_xlsx(&efforts, output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_export_html(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app export-html <input.json> <output.html>";
+
+    let [input, output] = args else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    let title = std::path::Path::new(input)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(input.clone());
+    match export_html(&efforts, &title, output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_export_gantt(args: &[String]) -> i32 {
+    const USAGE: &str =
+        "Uso: project_app export-gantt <input.json> <output.mmd|puml> [--mermaid|--plantuml]";
+
+    let mut files = vec![];
+    let mut syntax = None;
+    for arg in args.iter() {
+        match arg.as_str() {
+            "--mermaid" => syntax = Some(GanttSyntax::Mermaid),
+            "--plantuml" => syntax = Some(GanttSyntax::PlantUml),
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    let [input, output] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    let syntax = syntax.unwrap_or(GanttSyntax::from_path(output));
+    let title = std::path::Path::new(input)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(input.clone());
+    match export_gantt(&efforts, syntax, &title, output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_export_ics(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app export-ics <input.json> <output.ics|dir> [--worker <name>] [--per-worker]";
+
+    let mut files = vec![];
+    let mut worker = None;
+    let mut per_worker = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--per-worker" => per_worker = true,
+            "--worker" | "-w" => {
+                i += 1;
+                match args.get(i) {
+                    Some(name) => worker = Some(name.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [input, output] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    let result = if per_worker {
+        export_ics_per_worker(&efforts, output).map(|written| {
+            for path in written {
+                println!("{}", path.display());
+            }
+        })
+    } else {
+        if let Some(name) = worker.as_deref()
+            && !efforts.worker_names.iter().any(|w| w == name)
+        {
+            eprintln!("Worker sconosciuto: {}", name);
+            return 1;
+        }
+        export_ics(&efforts, worker.as_deref(), output)
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_anonymize(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app anonymize <input.json> <output.json> [--roles] [--mask-projects] [--reverse] [--mapping <map.json>]";
+
+    let mut files = vec![];
+    let mut options = AnonymizeOptions::default();
+    let mut reverse = false;
+    let mut mapping_path = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--roles" => options.pseudonym = Pseudonym::Role,
+            "--mask-projects" => options.mask_projects = true,
+            "--reverse" => reverse = true,
+            "--mapping" | "-m" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => mapping_path = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [input, output] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    // The mapping sits next to the anonymized plan
+    let anonymized = if reverse { input } else { output };
+    let mapping_path = mapping_path.unwrap_or_else(|| {
+        std::path::Path::new(anonymized)
+            .with_extension("mapping.json")
+            .to_string_lossy()
+            .to_string()
+    });
+
+    if !reverse {
+        return match export_anonymized(&efforts, &options, output, &mapping_path) {
+            Ok(()) => {
+                println!("Mappatura salvata in {}", mapping_path);
+                0
+            }
+            Err(e) => {
+                eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+                1
+            }
+        };
+    }
+
+    let mapping: AnonymizeMapping = match std::fs::read_to_string(&mapping_path)
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(mapping) => mapping,
+        Err(e) => {
+            eprintln!("Errore durante la lettura di \"{}\": {}", mapping_path, e);
+            return 1;
+        }
+    };
+    match save_efforts_to_file(&deanonymize(&efforts, &mapping), output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_digest(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app digest <input.json> [--week <YYYY-MM-DD>] [--weeks <N>] [--output <out.md>]";
+
+    let mut files = vec![];
+    let mut date = Utc::now().date_naive();
+    let mut horizon = 4;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--week" | "-w" => {
+                i += 1;
+                match args
+                    .get(i)
+                    .and_then(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d").ok())
+                {
+                    Some(d) => date = d,
+                    None => {
+                        eprintln!("Data non valida (formato atteso: YYYY-MM-DD)\n{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--weeks" | "-n" => {
+                i += 1;
+                match args.get(i).and_then(|v| v.parse::<i32>().ok()).filter(|n| *n > 0) {
+                    Some(n) => horizon = n,
+                    None => {
+                        eprintln!("Numero di settimane non valido\n{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [input] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    let week = local_to_days(&primo_giorno_settimana_corrente(&date));
+    let digest = render_digest(&efforts, week, horizon);
+    match output {
+        Some(path) => match std::fs::write(&path, digest) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Errore durante la scrittura di \"{}\": {}", path, e);
+                1
+            }
+        },
+        None => {
+            print!("{}", digest);
+            0
+        }
+    }
+}
+
+fn cmd_import_csv(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app import-csv <plan.json> <assignments.csv> [--replace] [--separator <c>] [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut mode = ImportMode::Merge;
+    let mut separator = None;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--replace" => mode = ImportMode::Replace,
+            "--separator" | "-s" => {
+                i += 1;
+                match args.get(i).and_then(|v| parse_char(v)) {
+                    Some(c) => separator = Some(c),
+                    None => {
+                        eprintln!("Separatore non valido\n{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [plan, input] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(mut efforts) = load(plan) else {
+        return 1;
+    };
+
+    let report = match import_csv_file(&mut efforts, input, mode, separator) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Errore durante la lettura di \"{}\": {}", input, e);
+            return 1;
+        }
+    };
+    print!("{}", report);
+
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_import_msp(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app import-msp <plan.json> <project.xml> [--mapping <map.json>] [--replace] [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut mode = ImportMode::Merge;
+    let mut mapping = None;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--replace" => mode = ImportMode::Replace,
+            "--mapping" | "-m" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => mapping = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [plan, input] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let mapping = match &mapping {
+        Some(path) => load_mapping(path),
+        None => mapping_for(input),
+    };
+    let mapping = match mapping {
+        Ok(mapping) => mapping,
+        Err(e) => {
+            eprintln!("Mappatura non valida: {}", e);
+            return 1;
+        }
+    };
+    let Some(mut efforts) = load(plan) else {
+        return 1;
+    };
+
+    let report = match import_msproject_file(&mut efforts, input, &mapping, mode) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Errore durante la lettura di \"{}\": {}", input, e);
+            return 1;
+        }
+    };
+    print!("{}", report);
+
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+/// Parses `name[:team,team]` against the projects of `other`. The project can
+/// also be given by id.
+fn parse_project_choice(other: &EffortsDto, text: &str) -> Result<ProjectSelection, String> {
+    let (name, teams) = match text.split_once(':') {
+        Some((name, teams)) => (name.trim(), Some(teams)),
+        None => (text.trim(), None),
+    };
+    let project = other
+        .projects
+        .iter()
+        .find(|p| p.text.trim().eq_ignore_ascii_case(name))
+        .or_else(|| {
+            let id = name.parse::<i32>().ok()?;
+            other.projects.iter().find(|p| p.project == id)
+        })
+        .ok_or_else(|| format!("Progetto non trovato: {}", name))?;
+
+    let mut selection = ProjectSelection {
+        project: project.project,
+        teams: vec![],
+    };
+    for team in teams.unwrap_or("").split(',').filter(|t| !t.trim().is_empty()) {
+        match Devs::from_title(team) {
+            Some(dev) => selection.teams.push(dev),
+            None => return Err(format!("Team non valido: {}", team.trim())),
+        }
+    }
+    Ok(selection)
+}
+
+fn cmd_import_projects(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app import-projects <plan.json> <other.json> [--project <name>[:<team>,...]]... [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut projects = vec![];
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--project" | "-p" => {
+                i += 1;
+                match args.get(i) {
+                    Some(project) => projects.push(project.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [plan, input] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let (Some(mut efforts), Some(other)) = (load(plan), load(input)) else {
+        return 1;
+    };
+
+    // Without a selection, show what can be imported
+    if projects.is_empty() {
+        for prj in other.projects.iter() {
+            let teams: Vec<String> = prj
+                .efforts
+                .iter()
+                .filter(|d| !d.is_empty())
+                .map(|d| format!("{} {}h", d.dev.title(), d.effort))
+                .collect();
+            println!("{}: {} [{}]", prj.project, prj.text, teams.join(", "));
+        }
+        return 0;
+    }
+
+    let mut selection = vec![];
+    for text in projects.iter() {
+        match parse_project_choice(&other, text) {
+            Ok(choice) => selection.push(choice),
+            Err(e) => {
+                eprintln!("{}\n{}", e, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    let report = import_projects(&mut efforts, &other, &selection);
+    print!("{}", report);
+
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_import_ics(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app import-ics <plan.json> <holidays.ics> [--worker <name>]... [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut workers = vec![];
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--worker" | "-w" => {
+                i += 1;
+                match args.get(i) {
+                    Some(name) => workers.push(name.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [plan, input] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(mut efforts) = load(plan) else {
+        return 1;
+    };
+
+    let report = match import_ics_file(&mut efforts, input, &workers) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Errore durante la lettura di \"{}\": {}", input, e);
+            return 1;
+        }
+    };
+    print!("{}", report);
+
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_holidays(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app holidays <plan.json> [--italian|--no-italian] [--patron <DD/MM>|--no-patron] [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut italian = None;
+    let mut patron = None;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--italian" => italian = Some(true),
+            "--no-italian" => italian = Some(false),
+            "--no-patron" => patron = Some(None),
+            "--patron" => {
+                i += 1;
+                match args.get(i).filter(|v| CalendarDto::parse_day(v).is_some()) {
+                    Some(day) => patron = Some(Some(day.clone())),
+                    None => {
+                        eprintln!("Giorno non valido (formato atteso: DD/MM)\n{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [plan] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(mut efforts) = load(plan) else {
+        return 1;
+    };
+
+    let changed = italian.is_some() || patron.is_some();
+    if let Some(enabled) = italian {
+        efforts.calendar.italian_holidays = enabled;
+    }
+    if let Some(day) = patron {
+        efforts.calendar.patron_saint = day;
+    }
+
+    // Public holidays within the weeks of the plan, then the stored ones
+    let (start_week, end_week) = efforts.start_end_weeks();
+    let (first, last) = (days_to_local(start_week), days_to_local(end_week));
+    for year in first.year()..=last.year() {
+        for (date, name) in efforts.calendar.holidays(year) {
+            if date >= first && date < last {
+                println!("{}  {}", date.format("%Y-%m-%d"), name);
+            }
+        }
+    }
+    for holiday in efforts.holidays.iter() {
+        let date = date_text(holiday.day);
+        if holiday.workers.is_empty() {
+            println!("{}  {}", date, holiday.name);
+        } else {
+            let workers = holiday.workers.join(", ");
+            println!("{}  {} ({})", date, holiday.name, workers);
+        }
+    }
+
+    if !changed {
+        return 0;
+    }
+    efforts.rebuild();
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+/// Asks on the terminal which side wins a conflict. `None` aborts the merge.
+fn ask_resolution(conflict: &crate::merge::Conflict) -> Option<Resolution> {
+    use std::io::Write;
+
+    loop {
+        println!("{}", conflict);
+        print!("Scegli [o] nostra, [t] loro, [b] base, [q] annulla: ");
+        std::io::stdout().flush().ok()?;
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).ok()? == 0 {
+            return None;
+        }
+        match answer.trim() {
+            "o" => return Some(Resolution::Ours),
+            "t" => return Some(Resolution::Theirs),
+            "b" => return Some(Resolution::Base),
+            "q" => return None,
+            _ => {}
+        }
+    }
+}
+
+fn cmd_merge(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app merge <base.json> <ours.json> <theirs.json> [--ours|--theirs] --output <out.json>";
+
+    let mut files = vec![];
+    let mut strategy = None;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--ours" => strategy = Some(Resolution::Ours),
+            "--theirs" => strategy = Some(Resolution::Theirs),
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    // The result never replaces one of the copies by default
+    let ([base, ours, theirs], Some(output)) = (files.as_slice(), output) else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let (Some(base_plan), Some(ours_plan), Some(theirs_plan)) =
+        (load(base), load(ours), load(theirs))
+    else {
+        return 1;
+    };
+
+    let mut result = match merge(&base_plan, &ours_plan, &theirs_plan) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    println!(
+        "Valori presi da \"{}\": {} - conflitti: {}",
+        theirs,
+        result.from_theirs,
+        result.conflicts.len()
+    );
+
+    if !result.conflicts.is_empty() {
+        let mut choices = vec![];
+        for conflict in result.conflicts.iter() {
+            match strategy.or_else(|| ask_resolution(conflict)) {
+                Some(choice) => choices.push(choice),
+                None => {
+                    eprintln!("Merge annullato, nessun file scritto");
+                    return 1;
+                }
+            }
+        }
+        resolve_conflicts(&mut result.merged, &result.conflicts, &choices);
+    }
+
+    match save_efforts_to_file(&result.merged, &output) {
+        Ok(()) => {
+            println!("Risultato scritto in \"{}\"", output);
+            0
+        }
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_diff(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app diff <old.json> <new.json>";
+
+    if let Some(arg) = args.iter().find(|a| a.starts_with('-')) {
+        eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+        return 2;
+    }
+    let [old, new] = args else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let (Some(old_plan), Some(new_plan)) = (load(old), load(new)) else {
+        return 1;
+    };
+
+    print!("{}", diff(&old_plan, &new_plan));
+    0
+}
+
+fn cmd_validate(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app validate <plan.json> [--fix] [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut apply_fixes = false;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--fix" => apply_fixes = true,
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [plan] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(mut efforts) = load(plan) else {
+        return 1;
+    };
+
+    let report = validate(&efforts);
+    print!("{}", report);
+    if !apply_fixes || report.fixable() == 0 {
+        return if report.is_empty() { 0 } else { 1 };
+    }
+
+    let fixed = fix(&mut efforts);
+    let left = validate(&efforts);
+    println!("Corretti: {}, rimasti: {}", fixed, left.problems.len());
+
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) if left.is_empty() => 0,
+        Ok(()) => 1,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_rename_worker(args: &[String]) -> i32 {
+    const USAGE: &str =
+        "Uso: project_app rename-worker <plan.json> <old name> <new name> [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let [plan, from, to] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(mut efforts) = load(plan) else {
+        return 1;
+    };
+
+    match rename_worker(&mut efforts, from, to) {
+        Ok(report) => print!("{}", report),
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    }
+
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+/// A change of `workers`, applied in command line order.
+enum RosterChange {
+    Add(String),
+    Retire(String, Option<i32>),
+    Team(String, Option<Devs>),
+    /// Intended load of a worker, or of the plan without a name.
+    Load(Option<String>, Option<LoadTargetDto>),
+    GroupByTeam,
+    Purge,
+}
+
+fn cmd_workers(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app workers <plan.json> [--add <name>] [--retire <name>=<YYYY-MM-DD>] [--unretire <name>] [--team <name>=<team>] [--load <name>=<target>[/<max>]] [--default-load <target>[/<max>]] [--group-by-team] [--purge] [--output <out.json>]";
+
+    let mut files = vec![];
+    let mut changes = vec![];
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let option = args[i].as_str();
+        let change = match option {
+            "--group-by-team" => Some(RosterChange::GroupByTeam),
+            "--purge" => Some(RosterChange::Purge),
+            "--add" | "--retire" | "--unretire" | "--team" | "--load" | "--default-load"
+            | "--output" | "-o" => {
+                i += 1;
+                let Some(value) = args.get(i) else {
+                    eprintln!("{}", USAGE);
+                    return 2;
+                };
+                let pair = value.rsplit_once('=');
+                match (option, pair) {
+                    ("--add", _) => Some(RosterChange::Add(value.clone())),
+                    ("--unretire", _) => Some(RosterChange::Retire(value.clone(), None)),
+                    ("--retire", Some((name, date))) => {
+                        match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                            Ok(date) => Some(RosterChange::Retire(
+                                name.to_string(),
+                                Some(local_to_days(&primo_giorno_settimana_corrente(&date))),
+                            )),
+                            Err(_) => {
+                                eprintln!("Data non valida (formato atteso: YYYY-MM-DD)\n{}", USAGE);
+                                return 2;
+                            }
+                        }
+                    }
+                    ("--team", Some((name, team))) => match Devs::from_title(team) {
+                        Some(dev) => Some(RosterChange::Team(name.to_string(), Some(dev))),
+                        None if team.is_empty() => Some(RosterChange::Team(name.to_string(), None)),
+                        None => {
+                            eprintln!("Team non riconosciuto: {}\n{}", team, USAGE);
+                            return 2;
+                        }
+                    },
+                    ("--load" | "--default-load", _) => {
+                        let (name, load) = match (option, pair) {
+                            ("--load", Some((name, load))) => (Some(name.to_string()), load),
+                            ("--default-load", _) => (None, value.as_str()),
+                            _ => {
+                                eprintln!("{}", USAGE);
+                                return 2;
+                            }
+                        };
+                        // An empty load goes back to the one of the plan
+                        match (!load.is_empty())
+                            .then(|| LoadTargetDto::parse(load))
+                            .transpose()
+                        {
+                            Ok(load) => Some(RosterChange::Load(name, load)),
+                            Err(e) => {
+                                eprintln!("{}\n{}", e, USAGE);
+                                return 2;
+                            }
+                        }
+                    }
+                    ("--output" | "-o", _) => {
+                        output = Some(value.clone());
+                        None
+                    }
+                    _ => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg if !arg.starts_with('-') => {
+                files.push(arg.to_string());
+                None
+            }
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        };
+        changes.extend(change);
+        i += 1;
+    }
+
+    let [plan] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(mut efforts) = load(plan) else {
+        return 1;
+    };
+
+    let changed = !changes.is_empty();
+    for change in changes.into_iter() {
+        match change {
+            RosterChange::Add(name) => {
+                if let Err(e) = add_worker(&mut efforts, &name) {
+                    eprintln!("{}", e);
+                    return 1;
+                }
+            }
+            RosterChange::Retire(name, from) => retire_worker(&mut efforts, &name, from),
+            RosterChange::Team(name, team) => set_team(&mut efforts, &name, team),
+            RosterChange::Load(name, load) => set_load(&mut efforts, name.as_deref(), load),
+            RosterChange::GroupByTeam => group_by_team(&mut efforts),
+            RosterChange::Purge => {
+                for name in purge_unused(&mut efforts) {
+                    println!("Rimosso: {}", name);
+                }
+            }
+        }
+    }
+
+    println!("Carico del piano: {}", efforts.load);
+    for (n, entry) in roster(&efforts).iter().enumerate() {
+        let mut line = format!(
+            "{:>3}. {} - {} - celle: {}",
+            n + 1,
+            entry.name,
+            entry.team.map_or("-", |t| t.title()),
+            entry.assignments
+        );
+        if entry.load_set {
+            line.push_str(&format!(" - carico {}", entry.load));
+        }
+        if let Some(day) = entry.retired_from {
+            line.push_str(&format!(" - ritirato dal {}", date_text(day)));
+        }
+        println!("{}", line);
+    }
+
+    if !changed {
+        return 0;
+    }
+    let output = output.unwrap_or(plan.clone());
+    match save_efforts_to_file(&efforts, &output) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_convert(args: &[String]) -> i32 {
+    const USAGE: &str =
+        "Uso: project_app convert <input.json|db> <output.json|db> [--compact|--pretty]";
+
+    let mut files = vec![];
+    let mut layout = FileLayout::Compact;
+    for arg in args.iter() {
+        match arg.as_str() {
+            "--compact" | "-c" => layout = FileLayout::Compact,
+            "--pretty" | "-p" => layout = FileLayout::Pretty,
+            arg if !arg.starts_with('-') => files.push(arg.to_string()),
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+    }
+
+    let [input, output] = files.as_slice() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    let Some(efforts) = load(input) else {
+        return 1;
+    };
+
+    // The database layout follows from the extension
+    let saved = if is_sqlite_path(output) {
+        save_efforts_to_file(&efforts, output)
+    } else {
+        save_efforts_with_layout(&efforts, output, layout)
+    };
+    match saved {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di {}: {}", output, e);
+            1
+        }
+    }
+}
+
+fn cmd_schema(args: &[String]) -> i32 {
+    const USAGE: &str = "Uso: project_app schema [--compact] [--output <schema.json>]";
+
+    let mut compact = false;
+    let mut output = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--compact" => compact = true,
+            "--output" | "-o" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => output = Some(path.clone()),
+                    None => {
+                        eprintln!("{}", USAGE);
+                        return 2;
+                    }
+                }
+            }
+            arg => {
+                eprintln!("Opzione non riconosciuta: {}\n{}", arg, USAGE);
+                return 2;
+            }
+        }
+        i += 1;
+    }
+
+    let schema = if compact {
+        compact_schema()
+    } else {
+        efforts_schema()
+    };
+    let json = render_schema(&schema);
+    let Some(output) = output else {
+        print!("{}", json);
+        return 0;
+    };
+    match std::fs::write(&output, json) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Errore durante la scrittura di \"{}\": {}", output, e);
+            1
+        }
+    }
+}
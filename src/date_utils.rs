@@ -48,6 +48,23 @@ pub fn days_to_local(days: i32) -> NaiveDate {
     NaiveDate::from_epoch_days(days).unwrap()
 }
 
+/// Date format of reports, exports and CLI messages.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Formats a number of days since Unix epoch as `YYYY-MM-DD` ([`DATE_FORMAT`]).
+///
+/// Falls back to the number itself when it is out of the valid range.
+///
+/// # Examples
+/// ```
+/// # use project_app::date_utils::date_text;
+/// assert_eq!(date_text(19723), "2024-01-01");
+/// ```
+pub fn date_text(days: i32) -> String {
+    NaiveDate::from_epoch_days(days)
+        .map_or_else(|| days.to_string(), |d| d.format(DATE_FORMAT).to_string())
+}
+
 /// Returns the first day (Monday) of the week for a given date.
 ///
 /// # Arguments
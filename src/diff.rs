@@ -0,0 +1,365 @@
+//! Semantic comparison of two effort plans.
+//!
+//! Instead of the nested JSON, the differences are reported in planning terms:
+//! projects added, removed or renamed, team budgets, and assignments per worker
+//! and week, followed by how the load of each worker changed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::date_utils::date_text;
+use crate::models::{Devs, EffortsDto};
+use crate::utils::{HOURS_PER_WEEK, get_hours};
+
+/// A single difference between two plans.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    ProjectAdded {
+        name: String,
+    },
+    ProjectRemoved {
+        name: String,
+    },
+    ProjectRenamed {
+        old: String,
+        new: String,
+    },
+    BudgetChanged {
+        project: String,
+        dev: Devs,
+        old: i32,
+        new: i32,
+    },
+    /// Percentage of a worker in a (project, team, week) cell. 0 means not assigned.
+    Assignment {
+        project: String,
+        dev: Devs,
+        worker: String,
+        week: i32,
+        old: i32,
+        new: i32,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::ProjectAdded { name } => write!(f, "+ progetto \"{}\"", name),
+            Change::ProjectRemoved { name } => write!(f, "- progetto \"{}\"", name),
+            Change::ProjectRenamed { old, new } => {
+                write!(f, "~ progetto \"{}\" rinominato in \"{}\"", old, new)
+            }
+            Change::BudgetChanged {
+                project,
+                dev,
+                old,
+                new,
+            } => write!(
+                f,
+                "~ budget {} / {}: {}h -> {}h",
+                project,
+                dev.title(),
+                old,
+                new
+            ),
+            Change::Assignment {
+                project,
+                dev,
+                worker,
+                week,
+                old,
+                new,
+            } => {
+                let sign = match (old, new) {
+                    (0, _) => '+',
+                    (_, 0) => '-',
+                    _ => '~',
+                };
+                write!(
+                    f,
+                    "{} {} {} / {} / {}: ",
+                    sign,
+                    date_text(*week),
+                    project,
+                    dev.title(),
+                    worker
+                )?;
+                match (old, new) {
+                    (0, new) => write!(f, "{}%", new),
+                    (old, 0) => write!(f, "{}%", old),
+                    (old, new) => write!(f, "{}% -> {}%", old, new),
+                }
+            }
+        }
+    }
+}
+
+/// Assigned hours of a worker over the whole plan, before and after.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerLoad {
+    pub worker: String,
+    pub old_hours: i32,
+    pub new_hours: i32,
+    /// Weeks in which the assignments of the worker changed.
+    pub weeks_changed: usize,
+}
+
+/// Outcome of [`diff`].
+#[derive(Debug, Clone, Default)]
+pub struct PlanDiff {
+    pub changes: Vec<Change>,
+    /// Only the workers whose assignments changed, sorted by name.
+    pub loads: Vec<WorkerLoad>,
+}
+
+impl PlanDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for PlanDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Nessuna differenza");
+        }
+        for change in self.changes.iter() {
+            writeln!(f, "{}", change)?;
+        }
+        if !self.loads.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Carico per worker:")?;
+            for load in self.loads.iter() {
+                writeln!(
+                    f,
+                    "  {}: {}h -> {}h ({:+}h, settimane modificate: {})",
+                    load.worker,
+                    load.old_hours,
+                    load.new_hours,
+                    load.new_hours - load.old_hours,
+                    load.weeks_changed
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Percentages keyed by (project, team, worker, week); a worker listed twice in a
+/// cell is summed.
+fn assignment_map(efforts: &EffortsDto) -> BTreeMap<(i32, Devs, String, i32), i32> {
+    let mut map = BTreeMap::new();
+    for a in efforts.assignments() {
+        *map.entry((a.project, a.dev, a.worker, a.week)).or_insert(0) += a.percent;
+    }
+    map
+}
+
+/// Compares two plans. Projects are matched by id.
+///
+/// # Arguments
+/// * `old` - The previous version of the plan
+/// * `new` - The current version of the plan
+pub fn diff(old: &EffortsDto, new: &EffortsDto) -> PlanDiff {
+    let mut result = PlanDiff::default();
+
+    let name = |id: i32| {
+        new.projects
+            .iter()
+            .chain(old.projects.iter())
+            .find(|p| p.project == id)
+            .map(|p| p.text.clone())
+            .unwrap_or_default()
+    };
+
+    for prj in new.projects.iter() {
+        match old.projects.iter().find(|p| p.project == prj.project) {
+            None => result.changes.push(Change::ProjectAdded {
+                name: prj.text.clone(),
+            }),
+            Some(old_prj) => {
+                if old_prj.text != prj.text {
+                    result.changes.push(Change::ProjectRenamed {
+                        old: old_prj.text.clone(),
+                        new: prj.text.clone(),
+                    });
+                }
+                for dev in prj.efforts.iter() {
+                    let old_effort = old_prj
+                        .efforts
+                        .iter()
+                        .find(|d| d.dev == dev.dev)
+                        .map_or(0, |d| d.effort);
+                    if old_effort != dev.effort {
+                        result.changes.push(Change::BudgetChanged {
+                            project: prj.text.clone(),
+                            dev: dev.dev,
+                            old: old_effort,
+                            new: dev.effort,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    for prj in old.projects.iter() {
+        if !new.projects.iter().any(|p| p.project == prj.project) {
+            result.changes.push(Change::ProjectRemoved {
+                name: prj.text.clone(),
+            });
+        }
+    }
+
+    let old_map = assignment_map(old);
+    let new_map = assignment_map(new);
+    let mut assignment_changes = vec![];
+    let mut loads: BTreeMap<String, WorkerLoad> = BTreeMap::new();
+    let mut changed_weeks: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+
+    let keys: BTreeSet<_> = old_map.keys().chain(new_map.keys()).collect();
+    for key in keys.into_iter() {
+        let (project, dev, worker, week) = key;
+        let old_percent = old_map.get(key).copied().unwrap_or(0);
+        let new_percent = new_map.get(key).copied().unwrap_or(0);
+        if old_percent == new_percent {
+            continue;
+        }
+        assignment_changes.push((
+            key,
+            Change::Assignment {
+                project: name(*project),
+                dev: *dev,
+                worker: worker.clone(),
+                week: *week,
+                old: old_percent,
+                new: new_percent,
+            },
+        ));
+        changed_weeks.entry(worker.clone()).or_default().push(*week);
+    }
+
+    // Sorted by week, then project, team and worker
+    assignment_changes.sort_by(|(a, _), (b, _)| (a.3, a.0, a.1, &a.2).cmp(&(b.3, b.0, b.1, &b.2)));
+    result
+        .changes
+        .extend(assignment_changes.into_iter().map(|(_, change)| change));
+
+    for (worker, mut weeks) in changed_weeks.into_iter() {
+        weeks.sort_unstable();
+        weeks.dedup();
+        let hours = |map: &BTreeMap<(i32, Devs, String, i32), i32>| {
+            map.iter()
+                .filter(|((_, _, w, _), _)| *w == worker)
+                .map(|(_, percent)| get_hours(*percent, HOURS_PER_WEEK))
+                .sum()
+        };
+        loads.insert(
+            worker.clone(),
+            WorkerLoad {
+                old_hours: hours(&old_map),
+                new_hours: hours(&new_map),
+                worker,
+                weeks_changed: weeks.len(),
+            },
+        );
+    }
+    result.loads = loads.into_values().collect();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        efforts.projects[0].efforts[0].datas[0].persons =
+            vec!["Mario|50".to_string(), "Lucia|30".to_string()];
+        efforts
+    }
+
+    #[test]
+    fn test_no_differences() {
+        let result = diff(&plan(), &plan());
+        assert!(result.is_empty());
+        assert_eq!(result.to_string(), "Nessuna differenza\n");
+    }
+
+    #[test]
+    fn test_assignments_and_loads() {
+        let old = plan();
+        let mut new = plan();
+        new.projects[0].efforts[0].datas[0].persons = vec!["Mario|100".to_string()];
+        new.projects[0].efforts[0].datas[1].persons = vec!["Anna|50".to_string()];
+        new.projects[0].efforts[0].effort = 80;
+        new.projects[0].text = "Alpha 2".to_string();
+
+        let result = diff(&old, &new);
+        let week0 = old.projects[0].efforts[0].datas[0].week;
+        assert_eq!(
+            result.changes,
+            vec![
+                Change::ProjectRenamed {
+                    old: "Alpha".to_string(),
+                    new: "Alpha 2".to_string()
+                },
+                Change::BudgetChanged {
+                    project: "Alpha 2".to_string(),
+                    dev: Devs::Mcsw,
+                    old: 0,
+                    new: 80
+                },
+                Change::Assignment {
+                    project: "Alpha 2".to_string(),
+                    dev: Devs::Mcsw,
+                    worker: "Lucia".to_string(),
+                    week: week0,
+                    old: 30,
+                    new: 0
+                },
+                Change::Assignment {
+                    project: "Alpha 2".to_string(),
+                    dev: Devs::Mcsw,
+                    worker: "Mario".to_string(),
+                    week: week0,
+                    old: 50,
+                    new: 100
+                },
+                Change::Assignment {
+                    project: "Alpha 2".to_string(),
+                    dev: Devs::Mcsw,
+                    worker: "Anna".to_string(),
+                    week: week0 + 7,
+                    old: 0,
+                    new: 50
+                },
+            ]
+        );
+
+        let mario = result.loads.iter().find(|l| l.worker == "Mario").unwrap();
+        assert_eq!(
+            (mario.old_hours, mario.new_hours, mario.weeks_changed),
+            (20, 40, 1)
+        );
+        let names: Vec<&str> = result.loads.iter().map(|l| l.worker.as_str()).collect();
+        assert_eq!(names, vec!["Anna", "Lucia", "Mario"]);
+    }
+
+    #[test]
+    fn test_projects_added_and_removed() {
+        let old = plan();
+        let mut new = EffortsDto::default();
+        new.projects[0].project = 1;
+        new.projects[0].text = "Beta".to_string();
+
+        let result = diff(&old, &new);
+        assert!(result.changes.contains(&Change::ProjectAdded {
+            name: "Beta".to_string()
+        }));
+        assert!(result.changes.contains(&Change::ProjectRemoved {
+            name: "Alpha".to_string()
+        }));
+    }
+}
@@ -0,0 +1,211 @@
+//! Anonymized copy of the plan, to share it outside the team.
+//!
+//! Every worker is replaced by a pseudonym (`Worker-01`…) or by their team role
+//! (`Hw-01`…), everywhere the name appears; project names can be masked too.
+//! Percentages are left untouched. The pseudonyms are returned in an
+//! [`AnonymizeMapping`], saved apart from the plan, which [`deanonymize`] uses
+//! to restore the real names.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::EffortsDto;
+use crate::workers::main_teams;
+
+/// How workers are renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pseudonym {
+    /// `Worker-01`, `Worker-02`… in the order of `worker_names`.
+    #[default]
+    Numbered,
+    /// The team where the worker has most of their assignments: `Hw-01`, `Pjm-01`…
+    Role,
+}
+
+/// Options of [`anonymize`].
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizeOptions {
+    pub pseudonym: Pseudonym,
+    /// Projects become `Project-01`, `Project-02`…
+    pub mask_projects: bool,
+}
+
+/// Pseudonyms used by [`anonymize`], from pseudonym to real name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AnonymizeMapping {
+    pub workers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub projects: BTreeMap<String, String>,
+}
+
+/// Replaces every occurrence of the names in `text`, longest names first so
+/// that "Mario Rossi" is not rewritten as "Mario".
+fn replace_names(text: &str, names: &HashMap<String, String>) -> String {
+    let mut sorted: Vec<(&String, &String)> = names.iter().collect();
+    sorted.sort_by_key(|(real, _)| std::cmp::Reverse(real.len()));
+    sorted
+        .into_iter()
+        .filter(|(real, _)| !real.is_empty())
+        .fold(text.to_string(), |text, (real, pseudonym)| {
+            text.replace(real.as_str(), pseudonym)
+        })
+}
+
+/// Returns an anonymized copy of the plan and the mapping to reverse it.
+///
+/// Holiday names mentioning a worker (e.g. imported leave) are rewritten too.
+///
+/// # Arguments
+/// * `efforts` - The plan to anonymize
+/// * `options` - Pseudonyms and project masking
+pub fn anonymize(
+    efforts: &EffortsDto,
+    options: &AnonymizeOptions,
+) -> (EffortsDto, AnonymizeMapping) {
+    let mut workers = efforts.worker_names.clone();
+    for a in efforts.assignments() {
+        if !workers.contains(&a.worker) {
+            workers.push(a.worker);
+        }
+    }
+
+    let roles = main_teams(efforts);
+    let mut counters: HashMap<&str, usize> = HashMap::new();
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut mapping = AnonymizeMapping::default();
+    for worker in workers.into_iter() {
+        let prefix = match options.pseudonym {
+            Pseudonym::Numbered => "Worker",
+            Pseudonym::Role => roles.get(&worker).map_or("Worker", |dev| dev.title()),
+        };
+        let counter = counters.entry(prefix).or_default();
+        *counter += 1;
+        let pseudonym = format!("{}-{:02}", prefix, counter);
+        mapping.workers.insert(pseudonym.clone(), worker.clone());
+        names.insert(worker, pseudonym);
+    }
+
+    let mut anonymized = efforts.clone();
+    anonymized.rename_workers(&names);
+    for holiday in anonymized.holidays.iter_mut() {
+        holiday.name = replace_names(&holiday.name, &names);
+    }
+
+    if options.mask_projects {
+        for (n, prj) in anonymized.projects.iter_mut().enumerate() {
+            let pseudonym = format!("Project-{:02}", n + 1);
+            mapping.projects.insert(
+                pseudonym.clone(),
+                std::mem::replace(&mut prj.text, pseudonym),
+            );
+        }
+    }
+
+    (anonymized, mapping)
+}
+
+/// Restores the real names of a plan anonymized with [`anonymize`].
+pub fn deanonymize(efforts: &EffortsDto, mapping: &AnonymizeMapping) -> EffortsDto {
+    let names: HashMap<String, String> = mapping
+        .workers
+        .iter()
+        .map(|(pseudonym, real)| (pseudonym.clone(), real.clone()))
+        .collect();
+
+    let mut restored = efforts.clone();
+    restored.rename_workers(&names);
+    for holiday in restored.holidays.iter_mut() {
+        holiday.name = replace_names(&holiday.name, &names);
+    }
+    for prj in restored.projects.iter_mut() {
+        if let Some(real) = mapping.projects.get(&prj.text) {
+            prj.text = real.clone();
+        }
+    }
+    restored
+}
+
+/// Writes an anonymized copy of the plan and, to a separate file, the mapping
+/// to reverse it.
+///
+/// # Returns
+/// * `Ok(())` - If both files were successfully written
+/// * `Err(std::io::Error)` - If there was an error writing a file
+pub fn export_anonymized(
+    efforts: &EffortsDto,
+    options: &AnonymizeOptions,
+    path: &str,
+    mapping_path: &str,
+) -> std::io::Result<()> {
+    let (anonymized, mapping) = anonymize(efforts, options);
+    crate::file_io::save_efforts_to_file(&anonymized, path)?;
+    std::fs::write(mapping_path, serde_json::to_string_pretty(&mapping)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::HolidayDto;
+
+    fn sample() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        let prj = &mut efforts.projects[0];
+        prj.efforts[3].datas[0].persons = vec!["Mario Rossi|50".to_string(), "Anna|30".to_string()];
+        prj.efforts[8].datas[1].persons = vec!["Mario Rossi|20".to_string()];
+        efforts.holidays.push(HolidayDto {
+            day: prj.start_week,
+            name: "Ferie Mario Rossi".to_string(),
+            workers: vec!["Mario Rossi".to_string()],
+        });
+        efforts.rebuild();
+        efforts
+    }
+
+    #[test]
+    fn test_numbered_pseudonyms_and_reverse() {
+        let efforts = sample();
+        let options = AnonymizeOptions {
+            pseudonym: Pseudonym::Numbered,
+            mask_projects: true,
+        };
+        let (anonymized, mapping) = anonymize(&efforts, &options);
+
+        assert_eq!(anonymized.worker_names, vec!["Worker-01", "Worker-02"]);
+        assert_eq!(
+            anonymized.projects[0].efforts[3].datas[0].persons,
+            vec!["Worker-01|50", "Worker-02|30"]
+        );
+        assert_eq!(anonymized.projects[0].text, "Project-01");
+        assert_eq!(anonymized.holidays[0].name, "Ferie Worker-01");
+        assert_eq!(anonymized.holidays[0].workers, vec!["Worker-01"]);
+        assert_eq!(mapping.workers["Worker-01"], "Mario Rossi");
+        assert_eq!(mapping.projects["Project-01"], "Alpha");
+
+        let restored = deanonymize(&anonymized, &mapping);
+        assert_eq!(restored.worker_names, efforts.worker_names);
+        assert_eq!(restored.projects[0].text, "Alpha");
+        assert_eq!(restored.holidays, efforts.holidays);
+        assert_eq!(restored.assignments(), efforts.assignments());
+    }
+
+    #[test]
+    fn test_role_pseudonyms() {
+        let options = AnonymizeOptions {
+            pseudonym: Pseudonym::Role,
+            mask_projects: false,
+        };
+        let efforts = sample();
+        let (anonymized, mapping) = anonymize(&efforts, &options);
+
+        assert_eq!(anonymized.worker_names, vec!["Hw-01", "Hw-02"]);
+        assert_eq!(anonymized.projects[0].text, "Alpha");
+        assert!(mapping.projects.is_empty());
+        // Percentages are untouched
+        assert_eq!(anonymized.assignments().len(), efforts.assignments().len());
+        for (a, b) in anonymized.sovra.iter().zip(efforts.sovra.iter()) {
+            assert_eq!(a.value, b.value);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! CSV export of the full allocation grid.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+
+use crate::date_utils::date_text;
+use crate::models::{Devs, EffortsDto};
+use crate::utils::HOURS_PER_WEEK;
+
+/// Layout of the exported CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvLayout {
+    /// One row per (project, team, worker, week, percent, hours).
+    List,
+    /// One row per (project, team, worker), with one column per week.
+    Matrix,
+}
+
+/// Separator and decimal mark used when writing the CSV.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub separator: u8,
+    pub decimal_comma: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            separator: b',',
+            decimal_comma: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Options understood by Excel with Italian locale: `;` separator and `,` decimal mark.
+    pub fn italian() -> Self {
+        Self {
+            separator: b';',
+            decimal_comma: true,
+        }
+    }
+
+    fn number(&self, value: f64) -> String {
+        let text = value.to_string();
+        if self.decimal_comma {
+            text.replace('.', ",")
+        } else {
+            text
+        }
+    }
+}
+
+/// Writes the allocation grid as CSV to the given writer.
+///
+/// # Arguments
+/// * `efforts` - The plan to export
+/// * `writer` - Destination of the CSV text
+/// * `layout` - List or matrix layout
+/// * `options` - Separator and decimal mark
+///
+/// # Examples
+/// ```
+/// # use project_app::exporters::{write_csv, CsvLayout, CsvOptions};
+/// # use project_app::models::EffortsDto;
+/// let mut out = vec![];
+/// write_csv(&EffortsDto::default(), &mut out, CsvLayout::List, &CsvOptions::default()).unwrap();
+/// assert!(String::from_utf8(out).unwrap().starts_with("project,team,worker,week,percent,hours"));
+/// ```
+pub fn write_csv<W: Write>(
+    efforts: &EffortsDto,
+    writer: W,
+    layout: CsvLayout,
+    options: &CsvOptions,
+) -> std::io::Result<()> {
+    let mut wtr = ::csv::WriterBuilder::new()
+        .delimiter(options.separator)
+        .from_writer(writer);
+
+    let assignments = efforts.assignments();
+
+    match layout {
+        CsvLayout::List => {
+            wtr.write_record(["project", "team", "worker", "week", "percent", "hours"])?;
+            for a in assignments.iter() {
+                let hours = a.percent as f64 * HOURS_PER_WEEK as f64 / 100.0;
+                wtr.write_record([
+                    a.project_name.clone(),
+                    a.dev.title().to_string(),
+                    a.worker.clone(),
+                    date_text(a.week),
+                    a.percent.to_string(),
+                    options.number(hours),
+                ])?;
+            }
+        }
+        CsvLayout::Matrix => {
+            let weeks: BTreeSet<i32> = efforts
+                .projects
+                .iter()
+                .flat_map(|p| p.efforts.iter())
+                .flat_map(|d| d.datas.iter())
+                .map(|d| d.week)
+                .collect();
+
+            // Keep the first-seen order of (project, team, worker)
+            let mut keys: Vec<(i32, String, Devs, String)> = vec![];
+            let mut values: HashMap<(i32, Devs, String), HashMap<i32, i32>> = HashMap::new();
+            for a in assignments.into_iter() {
+                let key = (a.project, a.dev, a.worker.clone());
+                let cells = values.entry(key).or_insert_with(|| {
+                    keys.push((a.project, a.project_name.clone(), a.dev, a.worker.clone()));
+                    HashMap::new()
+                });
+                *cells.entry(a.week).or_insert(0) += a.percent;
+            }
+
+            let mut header = vec!["project".to_string(), "team".into(), "worker".into()];
+            header.extend(weeks.iter().map(|w| date_text(*w)));
+            wtr.write_record(&header)?;
+
+            for (project, project_name, dev, worker) in keys.into_iter() {
+                let cells = &values[&(project, dev, worker.clone())];
+                let mut record = vec![project_name, dev.title().to_string(), worker];
+                record.extend(
+                    weeks
+                        .iter()
+                        .map(|w| cells.get(w).map_or(String::new(), |v| v.to_string())),
+                );
+                wtr.write_record(&record)?;
+            }
+        }
+    }
+
+    wtr.flush()
+}
+
+/// Exports the allocation grid to a CSV file.
+///
+/// # Arguments
+/// * `efforts` - The plan to export
+/// * `path` - File path of the CSV to create
+/// * `layout` - List or matrix layout
+/// * `options` - Separator and decimal mark
+///
+/// # Returns
+/// * `Ok(())` - If the file was successfully written
+/// * `Err(std::io::Error)` - If there was an error writing the file
+pub fn export_csv(
+    efforts: &EffortsDto,
+    path: &str,
+    layout: CsvLayout,
+    options: &CsvOptions,
+) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    write_csv(efforts, file, layout, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EffortByPrjDto;
+
+    fn sample() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        let mut prj = EffortByPrjDto::new(0);
+        prj.text = "Alpha".to_string();
+        prj.efforts[0].datas[0].persons = vec!["Mario|50".to_string(), "Lucia|33".to_string()];
+        prj.efforts[0].datas[1].persons = vec!["Mario|100".to_string()];
+        efforts.projects = vec![prj];
+        efforts
+    }
+
+    fn export(efforts: &EffortsDto, layout: CsvLayout, options: &CsvOptions) -> Vec<String> {
+        let mut out = vec![];
+        write_csv(efforts, &mut out, layout, options).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| l.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_list_layout() {
+        let efforts = sample();
+        let week = date_text(efforts.projects[0].efforts[0].datas[0].week);
+        let lines = export(&efforts, CsvLayout::List, &CsvOptions::default());
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], format!("Alpha,Mcsw,Mario,{},50,20", week));
+        assert_eq!(lines[2], format!("Alpha,Mcsw,Lucia,{},33,13.2", week));
+    }
+
+    #[test]
+    fn test_list_layout_italian() {
+        let lines = export(&sample(), CsvLayout::List, &CsvOptions::italian());
+        assert!(lines[0].starts_with("project;team;worker"));
+        assert!(lines[2].ends_with(";33;13,2"));
+    }
+
+    #[test]
+    fn test_matrix_layout() {
+        let efforts = sample();
+        let lines = export(&efforts, CsvLayout::Matrix, &CsvOptions::default());
+        // header + Mario + Lucia
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("Alpha,Mcsw,Mario,50,100,"));
+        assert!(lines[2].starts_with("Alpha,Mcsw,Lucia,33,,"));
+    }
+}
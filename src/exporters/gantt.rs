@@ -0,0 +1,234 @@
+//! Text Gantt charts of the plan, in Mermaid or PlantUML syntax.
+//!
+//! Every project is a section and every team with assignments a bar, from the
+//! first to the last week with someone assigned. The label carries the budget.
+
+use std::fmt::Write;
+
+use crate::date_utils::date_text;
+use crate::models::{Devs, EffortByPrjDto, EffortsDto};
+
+/// Syntax of the generated chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GanttSyntax {
+    Mermaid,
+    PlantUml,
+}
+
+impl GanttSyntax {
+    /// PlantUML for `.puml`, `.plantuml`, `.pu` and `.wsd` files, Mermaid otherwise.
+    ///
+    /// # Examples
+    /// ```
+    /// # use project_app::exporters::GanttSyntax;
+    /// assert_eq!(GanttSyntax::from_path("plan.puml"), GanttSyntax::PlantUml);
+    /// assert_eq!(GanttSyntax::from_path("plan.mmd"), GanttSyntax::Mermaid);
+    /// ```
+    pub fn from_path(path: &str) -> GanttSyntax {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("puml" | "plantuml" | "pu" | "wsd") => GanttSyntax::PlantUml,
+            _ => GanttSyntax::Mermaid,
+        }
+    }
+}
+
+/// One bar of the chart.
+#[derive(Debug, Clone, PartialEq)]
+struct GanttBar {
+    dev: Devs,
+    budget: i32,
+    start_week: i32,
+    end_week: i32,
+}
+
+impl GanttBar {
+    fn label(&self) -> String {
+        format!("{} ({}h)", self.dev.title(), self.budget)
+    }
+}
+
+/// The bars of the enabled teams of a project that have at least one assignment.
+fn bars(prj: &EffortByPrjDto) -> Vec<GanttBar> {
+    prj.efforts
+        .iter()
+        .filter(|d| d.enable)
+        .filter_map(|dev| {
+            let mut weeks = dev
+                .datas
+                .iter()
+                .filter(|d| d.get_total() > 0)
+                .map(|d| d.week);
+            let start_week = weeks.next()?;
+            let end_week = weeks.next_back().unwrap_or(start_week);
+            Some(GanttBar {
+                dev: dev.dev,
+                budget: dev.effort,
+                start_week,
+                end_week,
+            })
+        })
+        .collect()
+}
+
+/// The projects with at least one bar, in plan order.
+fn sections(efforts: &EffortsDto) -> Vec<(&str, Vec<GanttBar>)> {
+    efforts
+        .projects
+        .iter()
+        .map(|prj| (prj.text.trim(), bars(prj)))
+        .filter(|(_, bars)| !bars.is_empty())
+        .collect()
+}
+
+/// Removes the characters that end a name in the given syntax.
+fn clean(text: &str, syntax: GanttSyntax) -> String {
+    let reserved: &[char] = match syntax {
+        GanttSyntax::Mermaid => &[':', ';', '#', '\n'],
+        GanttSyntax::PlantUml => &['[', ']', '\n'],
+    };
+    text.chars()
+        .map(|c| if reserved.contains(&c) { ' ' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn render_mermaid(efforts: &EffortsDto, title: &str) -> String {
+    let mut out = String::new();
+    out.push_str("gantt\n");
+    let _ = writeln!(out, "    title {}", clean(title, GanttSyntax::Mermaid));
+    out.push_str("    dateFormat YYYY-MM-DD\n");
+    out.push_str("    axisFormat %d/%m\n");
+    for (name, bars) in sections(efforts).iter() {
+        let _ = writeln!(out, "    section {}", clean(name, GanttSyntax::Mermaid));
+        for bar in bars.iter() {
+            // The end date is exclusive: Saturday of the last week
+            let _ = writeln!(
+                out,
+                "    {} : {}, {}",
+                clean(&bar.label(), GanttSyntax::Mermaid),
+                date_text(bar.start_week),
+                date_text(bar.end_week + 5)
+            );
+        }
+    }
+    out
+}
+
+fn render_plantuml(efforts: &EffortsDto, title: &str) -> String {
+    let sections = sections(efforts);
+    let mut out = String::new();
+    out.push_str("@startgantt\n");
+    let _ = writeln!(out, "title {}", clean(title, GanttSyntax::PlantUml));
+    if let Some(start) = sections
+        .iter()
+        .flat_map(|(_, bars)| bars.iter().map(|b| b.start_week))
+        .min()
+    {
+        let _ = writeln!(out, "Project starts {}", date_text(start));
+    }
+    for (name, bars) in sections.iter() {
+        let name = clean(name, GanttSyntax::PlantUml);
+        let _ = writeln!(out, "-- {} --", name);
+        for bar in bars.iter() {
+            // Task names must be unique in the whole chart
+            let _ = writeln!(
+                out,
+                "[{} / {}] starts {} and ends {}",
+                name,
+                clean(&bar.label(), GanttSyntax::PlantUml),
+                date_text(bar.start_week),
+                date_text(bar.end_week + 4)
+            );
+        }
+    }
+    out.push_str("@endgantt\n");
+    out
+}
+
+/// Renders the plan as a Gantt chart.
+///
+/// # Arguments
+/// * `efforts` - The plan to export
+/// * `syntax` - Mermaid or PlantUML
+/// * `title` - Title of the chart, e.g. the file name
+pub fn render_gantt(efforts: &EffortsDto, syntax: GanttSyntax, title: &str) -> String {
+    match syntax {
+        GanttSyntax::Mermaid => render_mermaid(efforts, title),
+        GanttSyntax::PlantUml => render_plantuml(efforts, title),
+    }
+}
+
+/// Exports the plan to a Gantt chart file.
+///
+/// # Returns
+/// * `Ok(())` - If the file was successfully written
+/// * `Err(std::io::Error)` - If there was an error writing the file
+pub fn export_gantt(
+    efforts: &EffortsDto,
+    syntax: GanttSyntax,
+    title: &str,
+    path: &str,
+) -> std::io::Result<()> {
+    std::fs::write(path, render_gantt(efforts, syntax, title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        let prj = &mut efforts.projects[0];
+        prj.text = "Alpha: board".to_string();
+        prj.efforts[3].effort = 120;
+        prj.efforts[3].datas[1].persons = vec!["Mario|50".to_string()];
+        prj.efforts[3].datas[3].persons = vec!["Lucia|20".to_string()];
+        // A budget without assignments has no bar
+        prj.efforts[4].effort = 80;
+        efforts.rebuild();
+        efforts
+    }
+
+    #[test]
+    fn test_bars_span_the_assigned_weeks() {
+        let efforts = sample();
+        let weeks: Vec<i32> = efforts.projects[0].efforts[3]
+            .datas
+            .iter()
+            .map(|d| d.week)
+            .collect();
+        assert_eq!(
+            bars(&efforts.projects[0]),
+            vec![GanttBar {
+                dev: Devs::Hw,
+                budget: 120,
+                start_week: weeks[1],
+                end_week: weeks[3],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_mermaid_and_plantuml() {
+        let efforts = sample();
+        let week = efforts.projects[0].efforts[3].datas[1].week;
+
+        let mermaid = render_gantt(&efforts, GanttSyntax::Mermaid, "plan");
+        assert!(mermaid.starts_with("gantt\n"));
+        assert!(mermaid.contains("    section Alpha  board\n"));
+        assert!(mermaid.contains(&format!("    Hw (120h) : {}, ", date_text(week))));
+
+        let plantuml = render_gantt(&efforts, GanttSyntax::PlantUml, "plan");
+        assert!(plantuml.starts_with("@startgantt\n"));
+        assert!(plantuml.contains("-- Alpha: board --\n"));
+        assert!(plantuml.contains(&format!(
+            "[Alpha: board / Hw (120h)] starts {}",
+            date_text(week)
+        )));
+        assert!(plantuml.ends_with("@endgantt\n"));
+    }
+}
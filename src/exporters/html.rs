@@ -0,0 +1,266 @@
+//! Self-contained HTML report of the plan.
+//!
+//! The report has no external assets: styles are inlined, so the single file can be
+//! attached to a mail or opened from a shared folder.
+
+use std::fmt::Write;
+
+use crate::date_utils::date_text;
+use crate::models::{EffortByPrjDto, EffortsDto, LoadLevel};
+
+const STYLE: &str = "body{font-family:sans-serif;font-size:13px;margin:16px}\
+h1{font-size:20px}h2{font-size:16px;margin-top:28px}\
+table{border-collapse:collapse;margin:6px 0}\
+th,td{border:1px solid #bbb;padding:2px 6px;text-align:center;white-space:nowrap}\
+th{background:#e6e6e6}td.team{font-weight:bold;text-align:left}\
+td.alarm{background:#ff0000;color:#fff}td.name{text-align:left}\
+.scroll{overflow-x:auto}ul.problems li{margin:2px 0}";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn project_section(out: &mut String, prj: &EffortByPrjDto) {
+    let _ = writeln!(out, "<h2>{}</h2>", escape(&prj.text));
+
+    // Budgets and consumption
+    out.push_str("<table><tr><th>Team</th><th>Budget</th><th>Consumed</th><th>Remains</th></tr>\n");
+    for dev in prj.efforts.iter().filter(|d| d.enable) {
+        let alarm = (dev.remains == dev.effort && dev.effort != 0) || dev.remains < 0;
+        let _ = writeln!(
+            out,
+            "<tr><td class=\"team\" style=\"background:#{:06x};color:#{:06x}\">{}</td><td>{}</td><td>{}</td><td{}>{}</td></tr>",
+            dev.dev.color(),
+            dev.dev.text_color(),
+            dev.dev.title(),
+            dev.effort,
+            dev.total,
+            if alarm { " class=\"alarm\"" } else { "" },
+            dev.remains
+        );
+    }
+    out.push_str("</table>\n");
+
+    // Week grid with the running totals
+    let Some(first) = prj.efforts.first() else {
+        return;
+    };
+    out.push_str("<div class=\"scroll\"><table><tr><th>Team</th>");
+    for data in first.datas.iter() {
+        let _ = write!(out, "<th>{}</th>", date_text(data.week));
+    }
+    out.push_str("</tr>\n");
+    for dev in prj.efforts.iter().filter(|d| d.enable) {
+        let _ = write!(
+            out,
+            "<tr><td class=\"team\" style=\"background:#{:06x};color:#{:06x}\">{}</td>",
+            dev.dev.color(),
+            dev.dev.text_color(),
+            dev.dev.title()
+        );
+        for data in dev.datas.iter() {
+            let workers: Vec<String> = data
+                .persons
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| escape(p))
+                .collect();
+            let title = if workers.is_empty() {
+                String::new()
+            } else {
+                format!(" title=\"{}\"", workers.join("&#10;"))
+            };
+            match data.consumption_color() {
+                Some(color) => {
+                    let _ = write!(
+                        out,
+                        "<td{} style=\"color:#{:06x};font-weight:bold\">{}</td>",
+                        title, color, data.total
+                    );
+                }
+                None => {
+                    let _ = write!(out, "<td{}>{}</td>", title, data.total);
+                }
+            }
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table></div>\n");
+}
+
+fn sovra_section(out: &mut String, efforts: &EffortsDto) {
+    out.push_str("<h2>Over-allocation</h2>\n<div class=\"scroll\"><table><tr><th>Worker</th>");
+    for sovra in efforts.sovra.iter() {
+        let _ = write!(out, "<th>{}</th>", date_text(sovra.week));
+    }
+    out.push_str("</tr>\n");
+    let first_week = efforts.sovra.first().map_or(0, |s| s.week);
+    for (n, worker) in efforts.worker_names.iter().enumerate() {
+        // As in the footer: no row when retired before the first week
+        if efforts.is_retired(worker, first_week) {
+            continue;
+        }
+        let load = efforts.load_target(worker);
+        let _ = write!(out, "<tr><td class=\"name\">{}</td>", escape(worker));
+        for sovra in efforts.sovra.iter() {
+            if efforts.is_retired(worker, sovra.week) {
+                out.push_str("<td></td>");
+                continue;
+            }
+            let value = sovra.value.get(n).copied().unwrap_or(0);
+            let _ = write!(
+                out,
+                "<td style=\"background:#{:06x}\">{}</td>",
+                LoadLevel::from_load(value, &load).color(),
+                value
+            );
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table></div>\n");
+}
+
+fn problems_section(out: &mut String, efforts: &EffortsDto) {
+    let mut problems = vec![];
+    for prj in efforts.projects.iter() {
+        for dev in prj.efforts.iter().filter(|d| d.remains < 0) {
+            problems.push(format!(
+                "{} / {}: remains {}",
+                escape(&prj.text),
+                dev.dev.title(),
+                dev.remains
+            ));
+        }
+    }
+    for (n, worker) in efforts.worker_names.iter().enumerate() {
+        let load = efforts.load_target(worker);
+        for sovra in efforts.sovra.iter() {
+            let value = sovra.value.get(n).copied().unwrap_or(0);
+            if !efforts.is_retired(worker, sovra.week)
+                && LoadLevel::from_load(value, &load) == LoadLevel::Over
+            {
+                problems.push(format!(
+                    "{}: {}% (max {}%) in week {}",
+                    escape(worker),
+                    value,
+                    load.max,
+                    date_text(sovra.week)
+                ));
+            }
+        }
+    }
+
+    out.push_str("<h2>Problems</h2>\n");
+    if problems.is_empty() {
+        out.push_str("<p>No problems found.</p>\n");
+        return;
+    }
+    out.push_str("<ul class=\"problems\">\n");
+    for problem in problems.iter() {
+        let _ = writeln!(out, "<li>{}</li>", problem);
+    }
+    out.push_str("</ul>\n");
+}
+
+/// Renders the plan as a self-contained HTML page.
+///
+/// Totals and over-allocation are recalculated first, so the report is
+/// consistent even if the file was saved with stale values.
+///
+/// # Arguments
+/// * `efforts` - The plan to report on
+/// * `title` - Title of the page, e.g. the file name
+pub fn render_html(efforts: &EffortsDto, title: &str) -> String {
+    let mut efforts = efforts.clone();
+    efforts.rebuild();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>",
+        escape(title),
+        STYLE
+    );
+    let _ = writeln!(
+        out,
+        "<h1>{}</h1>\n<p>Generated on {}</p>",
+        escape(title),
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    );
+
+    problems_section(&mut out, &efforts);
+    for prj in efforts.projects.iter() {
+        project_section(&mut out, prj);
+    }
+    sovra_section(&mut out, &efforts);
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Exports the plan to a self-contained HTML file.
+///
+/// # Returns
+/// * `Ok(())` - If the file was successfully written
+/// * `Err(std::io::Error)` - If there was an error writing the file
+pub fn export_html(efforts: &EffortsDto, title: &str, path: &str) -> std::io::Result<()> {
+    std::fs::write(path, render_html(efforts, title))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+
+    #[test]
+    fn test_render_reports_problems() {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "R&D <core>".to_string();
+        efforts.projects[0].efforts[0].effort = 10;
+        efforts.projects[0].efforts[0].datas[0].persons =
+            vec!["Mario|90".to_string(), "Lucia|20".to_string()];
+
+        let html = render_html(&efforts, "plan.json");
+        assert!(html.contains("<h2>R&amp;D &lt;core&gt;</h2>"));
+        // 36h + 8h consumed on a budget of 10
+        assert!(html.contains("R&amp;D &lt;core&gt; / Mcsw: remains -34"));
+        assert!(html.contains("Mario: 90% (max 80%) in week"));
+        assert!(!html.contains("Lucia: 20%"));
+        assert!(!html.contains("http"));
+    }
+
+    #[test]
+    fn test_render_leaves_out_retired_workers() {
+        let mut efforts = EffortsDto::default();
+        let datas = &mut efforts.projects[0].efforts[0].datas;
+        datas[0].persons = vec!["Mario|90".to_string(), "Lucia|90".to_string()];
+        datas[1].persons = vec!["Mario|90".to_string()];
+        efforts.rebuild();
+        let (first, second) = (efforts.sovra[0].week, efforts.sovra[1].week);
+        efforts.worker_mut("Lucia").retired_from = Some(first);
+        efforts.worker_mut("Mario").retired_from = Some(second);
+
+        let html = render_html(&efforts, "plan.json");
+        assert!(!html.contains("<td class=\"name\">Lucia</td>"));
+        assert!(!html.contains("Lucia: 90%"));
+        assert!(html.contains("<td class=\"name\">Mario</td>"));
+        assert!(html.contains(&format!(
+            "Mario: 90% (max 80%) in week {}",
+            date_text(first)
+        )));
+        assert!(!html.contains(&format!("in week {}", date_text(second))));
+    }
+
+    #[test]
+    fn test_render_without_problems() {
+        let html = render_html(&EffortsDto::default(), "empty");
+        assert!(html.contains("No problems found."));
+    }
+}
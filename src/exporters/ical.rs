@@ -0,0 +1,187 @@
+//! iCalendar (.ics) export of the workers' assignments.
+//!
+//! Each run of consecutive weeks on the same project, team and percentage becomes
+//! an all-day event from Monday to Friday.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+
+use crate::date_utils::days_to_local;
+use crate::models::{AssignmentRun, EffortsDto, assignment_runs};
+
+/// Escapes a TEXT value (RFC 5545, 3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Appends a content line, folded at 75 octets (RFC 5545, 3.1).
+fn push_line(out: &mut String, line: &str) {
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn date(days: i32) -> String {
+    days_to_local(days).format("%Y%m%d").to_string()
+}
+
+fn uid(run: &AssignmentRun) -> String {
+    let worker: String = run
+        .worker
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    format!(
+        "{}-{:?}-{}-{}-{}@project_app",
+        run.project, run.dev, worker, run.start_week, run.percent
+    )
+}
+
+/// Renders the assignments as an iCalendar document.
+///
+/// # Arguments
+/// * `efforts` - The plan to export
+/// * `worker` - Only this worker's assignments, or every worker when `None`
+pub fn render_ics(efforts: &EffortsDto, worker: Option<&str>) -> String {
+    let assignments: Vec<_> = efforts
+        .assignments()
+        .into_iter()
+        .filter(|a| worker.is_none_or(|w| a.worker == w))
+        .collect();
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//project_app//Effort Tracker//IT");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    if let Some(worker) = worker {
+        push_line(&mut out, &format!("X-WR-CALNAME:{}", escape(worker)));
+    }
+
+    for run in assignment_runs(&assignments, true).iter() {
+        let mut summary = format!(
+            "{} / {} {}%",
+            run.project_name,
+            run.dev.title(),
+            run.percent
+        );
+        if worker.is_none() {
+            summary = format!("{} – {}", run.worker, summary);
+        }
+        let description = format!(
+            "{}: {}% on {} / {}",
+            run.worker,
+            run.percent,
+            run.project_name,
+            run.dev.title()
+        );
+
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", uid(run)));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        push_line(
+            &mut out,
+            &format!("DTSTART;VALUE=DATE:{}", date(run.start_week)),
+        );
+        // The end date is exclusive: Saturday of the last week
+        push_line(
+            &mut out,
+            &format!("DTEND;VALUE=DATE:{}", date(run.end_week + 5)),
+        );
+        push_line(&mut out, &format!("SUMMARY:{}", escape(&summary)));
+        push_line(&mut out, &format!("DESCRIPTION:{}", escape(&description)));
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Exports the assignments to a single `.ics` file.
+///
+/// # Returns
+/// * `Ok(())` - If the file was successfully written
+/// * `Err(std::io::Error)` - If there was an error writing the file
+pub fn export_ics(efforts: &EffortsDto, worker: Option<&str>, path: &str) -> std::io::Result<()> {
+    std::fs::write(path, render_ics(efforts, worker))
+}
+
+/// Exports one `.ics` file per worker of `worker_names` into a directory.
+///
+/// # Returns
+/// * `Ok(Vec<PathBuf>)` - The files written
+/// * `Err(std::io::Error)` - If there was an error writing a file
+pub fn export_ics_per_worker(efforts: &EffortsDto, dir: &str) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = vec![];
+    for worker in efforts.worker_names.iter() {
+        let file_name: String = worker
+            .chars()
+            .map(|c| if "<>:\"/\\|?*".contains(c) { '_' } else { c })
+            .collect();
+        let path = Path::new(dir).join(format!("{}.ics", file_name.trim()));
+        std::fs::write(&path, render_ics(efforts, Some(worker)))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        let datas = &mut efforts.projects[0].efforts[0].datas;
+        datas[0].persons = vec!["Mario|50".to_string(), "Lucia|20".to_string()];
+        datas[1].persons = vec!["Mario|50".to_string()];
+        datas[2].persons = vec!["Mario|80".to_string()];
+        datas[4].persons = vec!["Mario|80".to_string()];
+        efforts
+    }
+
+    #[test]
+    fn test_runs_become_events() {
+        let efforts = sample();
+        let ics = render_ics(&efforts, Some("Mario"));
+        // 50% on weeks 0-1, 80% on week 2, 80% again on week 4 after a gap
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 3);
+        assert!(ics.contains("SUMMARY:Alpha / Mcsw 50%\r\n"));
+        assert!(!ics.contains("Lucia"));
+
+        let start = efforts.projects[0].efforts[0].datas[0].week;
+        assert!(ics.contains(&format!("DTSTART;VALUE=DATE:{}\r\n", date(start))));
+        assert!(ics.contains(&format!("DTEND;VALUE=DATE:{}\r\n", date(start + 12))));
+    }
+
+    #[test]
+    fn test_combined_calendar() {
+        let ics = render_ics(&sample(), None);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 4);
+        assert!(ics.contains("SUMMARY:Lucia – Alpha / Mcsw 20%"));
+    }
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape("a,b;c\\d"), "a\\,b\\;c\\\\d");
+        let mut out = String::new();
+        push_line(&mut out, &"x".repeat(100));
+        let lines: Vec<&str> = out.split("\r\n").collect();
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1], format!(" {}", "x".repeat(25)));
+    }
+}
@@ -0,0 +1,262 @@
+//! Markdown weekly status digest.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::date_utils::date_text;
+use crate::models::{EffortsDto, LoadLevel, assignment_runs};
+
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Renders the status digest of a week as Markdown.
+///
+/// The digest lists who works on what in the week, the workers above their
+/// maximum load or below half their target, the teams whose remaining budget
+/// is negative in that week and the assignments starting or ending in the
+/// following `horizon_weeks` weeks. The load is the over-allocation of the
+/// footer, scaled to the working days left by holidays and closures.
+///
+/// # Arguments
+/// * `efforts` - The plan to summarize
+/// * `week` - Monday of the week, in days since Unix epoch
+/// * `horizon_weeks` - Number of weeks to look ahead for starting/ending assignments
+pub fn render_digest(efforts: &EffortsDto, week: i32, horizon_weeks: i32) -> String {
+    let mut efforts = efforts.clone();
+    efforts.rebuild();
+    let assignments = efforts.assignments();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# Weekly status – week of {}\n", date_text(week));
+
+    // Who works on what
+    let sovra = efforts.sovra.iter().find(|s| s.week == week);
+    let load: BTreeMap<&str, i32> = efforts
+        .worker_names
+        .iter()
+        .enumerate()
+        .map(|(n, w)| {
+            let value = sovra.and_then(|s| s.value.get(n)).copied().unwrap_or(0);
+            (w.as_str(), value)
+        })
+        .collect();
+    let mut by_worker: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for a in assignments.iter().filter(|a| a.week == week) {
+        by_worker.entry(&a.worker).or_default().push(format!(
+            "{} / {} {}%",
+            a.project_name,
+            a.dev.title(),
+            a.percent
+        ));
+    }
+
+    out.push_str("## Assignments\n\n");
+    if by_worker.is_empty() {
+        out.push_str("No assignments in this week.\n");
+    } else {
+        out.push_str("| Worker | Load | Assignments |\n|---|---:|---|\n");
+        for (worker, items) in by_worker.iter() {
+            let _ = writeln!(
+                out,
+                "| {} | {}% | {} |",
+                cell(worker),
+                load[worker],
+                cell(&items.join(", "))
+            );
+        }
+    }
+
+    // Load alerts
+    out.push_str("\n## Load alerts\n\n");
+    let levels: Vec<_> = load
+        .iter()
+        .filter(|(worker, _)| !efforts.is_retired(worker, week))
+        .map(|(worker, value)| {
+            let target = efforts.load_target(worker);
+            (worker, value, target, LoadLevel::from_load(*value, &target))
+        })
+        .collect();
+    let over: Vec<_> = levels
+        .iter()
+        .filter(|(.., level)| *level == LoadLevel::Over)
+        .collect();
+    let under: Vec<_> = levels
+        .iter()
+        .filter(|(.., level)| *level == LoadLevel::Low)
+        .collect();
+    if over.is_empty() && under.is_empty() {
+        out.push_str("All workers are within their intended load.\n");
+    }
+    if !over.is_empty() {
+        out.push_str("Over their maximum:\n\n");
+        for (worker, value, target, _) in over {
+            let _ = writeln!(out, "- **{}**: {}% (max {}%)", worker, value, target.max);
+        }
+        out.push('\n');
+    }
+    if !under.is_empty() {
+        out.push_str("Under half their target:\n\n");
+        for (worker, value, target, _) in under {
+            let _ = writeln!(out, "- {}: {}% (target {}%)", worker, value, target.target);
+        }
+    }
+
+    // Negative remains in this week
+    out.push_str("\n## Negative remains\n\n");
+    let mut negative = false;
+    for prj in efforts.projects.iter() {
+        for dev in prj.efforts.iter() {
+            let Some(index) = dev.datas.iter().position(|d| d.week == week) else {
+                continue;
+            };
+            let remains = dev.datas[index].remains;
+            if remains >= 0 {
+                continue;
+            }
+            negative = true;
+            let new = index == 0 || dev.datas[index - 1].remains >= 0;
+            let _ = writeln!(
+                out,
+                "- {} / {}: {} h{}",
+                prj.text,
+                dev.dev.title(),
+                remains,
+                if new { " (new this week)" } else { "" }
+            );
+        }
+    }
+    if !negative {
+        out.push_str("No team is over budget.\n");
+    }
+
+    // Assignments starting or ending soon
+    let horizon = week + horizon_weeks * 7;
+    let runs = assignment_runs(&assignments, false);
+    let _ = writeln!(
+        out,
+        "\n## Starting or ending in the next {} weeks\n",
+        horizon_weeks
+    );
+    let mut upcoming = false;
+    for run in runs
+        .iter()
+        .filter(|r| r.start_week >= week && r.start_week < horizon)
+    {
+        upcoming = true;
+        let _ = writeln!(
+            out,
+            "- {} starts: {} on {} / {} (until {})",
+            date_text(run.start_week),
+            run.worker,
+            run.project_name,
+            run.dev.title(),
+            date_text(run.end_week)
+        );
+    }
+    for run in runs
+        .iter()
+        .filter(|r| r.end_week >= week && r.end_week < horizon)
+    {
+        upcoming = true;
+        let _ = writeln!(
+            out,
+            "- {} ends: {} on {} / {} (since {})",
+            date_text(run.end_week),
+            run.worker,
+            run.project_name,
+            run.dev.title(),
+            date_text(run.start_week)
+        );
+    }
+    if !upcoming {
+        out.push_str("Nothing starts or ends.\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{HolidayDto, LoadTargetDto};
+
+    fn sample() -> (EffortsDto, i32) {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        let datas = &mut efforts.projects[0].efforts[0].datas;
+        datas[0].persons = vec!["Mario|90".to_string(), "Lucia|20".to_string()];
+        datas[1].persons = vec!["Mario|90".to_string()];
+        datas[2].persons = vec!["Anna|50".to_string()];
+        let week = datas[0].week;
+        (efforts, week)
+    }
+
+    #[test]
+    fn test_digest_assignments_and_alerts() {
+        let (efforts, week) = sample();
+        let md = render_digest(&efforts, week, 4);
+        assert!(md.contains("| Mario | 90% | Alpha / Mcsw 90% |"));
+        assert!(md.contains("- **Mario**: 90% (max 80%)"));
+        assert!(md.contains("- Lucia: 20% (target 80%)"));
+        // Anna has no assignment in this week
+        assert!(md.contains("- Anna: 0%"));
+        // Budget is 0, so the team is over budget from the first week
+        assert!(md.contains("- Alpha / Mcsw: -44 h (new this week)"));
+    }
+
+    #[test]
+    fn test_digest_load_counts_holidays() {
+        let (mut efforts, week) = sample();
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|60".to_string()];
+        // Mario is off three days: 60% of the two days left is 150%
+        for day in 0..3 {
+            efforts.holidays.push(HolidayDto {
+                day: week + day,
+                name: "Ferie".to_string(),
+                workers: vec!["Mario".to_string()],
+            });
+        }
+        let md = render_digest(&efforts, week, 4);
+        assert!(md.contains("| Mario | 150% | Alpha / Mcsw 60% |"));
+        assert!(md.contains("- **Mario**: 150% (max 80%)"));
+    }
+
+    #[test]
+    fn test_digest_alerts_use_the_worker_load() {
+        let (mut efforts, week) = sample();
+        // Lucia is part-time, Mario has no overhead
+        efforts.worker_mut("Lucia").load = Some(LoadTargetDto::new(30, 40).unwrap());
+        efforts.load = LoadTargetDto::new(100, 100).unwrap();
+        let md = render_digest(&efforts, week, 4);
+        assert!(!md.contains("Lucia: 20%"));
+        assert!(!md.contains("**Mario**"));
+        assert!(md.contains("- Anna: 0% (target 100%)"));
+    }
+
+    #[test]
+    fn test_digest_leaves_out_retired_workers() {
+        let (mut efforts, week) = sample();
+        efforts.worker_mut("Anna").retired_from = Some(week);
+        let md = render_digest(&efforts, week, 4);
+        assert!(!md.contains("- Anna: 0%"));
+        assert!(md.contains("- Lucia: 20% (target 80%)"));
+
+        // Still flagged before the retirement
+        efforts.worker_mut("Anna").retired_from = Some(week + 7);
+        assert!(render_digest(&efforts, week, 4).contains("- Anna: 0%"));
+    }
+
+    #[test]
+    fn test_digest_starting_and_ending() {
+        let (efforts, week) = sample();
+        let md = render_digest(&efforts, week, 4);
+        let start = date_text(week + 14);
+        let end = date_text(week + 7);
+        assert!(md.contains(&format!("- {} starts: Anna on Alpha / Mcsw", start)));
+        assert!(md.contains(&format!("- {} ends: Mario on Alpha / Mcsw", end)));
+
+        let md = render_digest(&efforts, week, 1);
+        assert!(!md.contains("Anna on Alpha"));
+    }
+}
@@ -0,0 +1,23 @@
+//! Exports of the effort plan to formats readable by other tools.
+//!
+//! Every exporter works on an [`EffortsDto`](crate::models::EffortsDto), so it can
+//! be used both from the GUI and from the command line.
+
+pub mod anonymize;
+pub mod csv;
+pub mod gantt;
+pub mod html;
+pub mod ical;
+pub mod markdown;
+pub mod xlsx;
+
+// Re-export commonly used items
+pub use anonymize::{
+    AnonymizeMapping, AnonymizeOptions, Pseudonym, anonymize, deanonymize, export_anonymized,
+};
+pub use csv::{CsvLayout, CsvOptions, export_csv, write_csv};
+pub use gantt::{GanttSyntax, export_gantt, render_gantt};
+pub use html::{export_html, render_html};
+pub use ical::{export_ics, export_ics_per_worker, render_ics};
+pub use markdown::render_digest;
+pub use xlsx::export_xlsx;
@@ -0,0 +1,249 @@
+//! Excel (.xlsx) export of the plan, coloured like the GUI.
+//!
+//! The workbook has one sheet per project, with a block of rows per team, and a
+//! last sheet with the over-allocation of each worker per week.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use rust_xlsxwriter::{Color, Format, FormatAlign, FormatBorder, Workbook, Worksheet, XlsxError};
+
+use crate::date_utils::date_text;
+use crate::models::{EffortByPrjDto, EffortsDto, LoadLevel};
+
+const SOVRA_SHEET: &str = "Sovra";
+/// Columns before the first week: team, budget, consumed, remains.
+const FIRST_WEEK_COL: u16 = 4;
+
+/// Builds the workbook for the given plan.
+///
+/// Totals and over-allocation are recalculated first, so the sheets are
+/// consistent even if the file was saved with stale values.
+pub fn build_workbook(efforts: &EffortsDto) -> Result<Workbook, XlsxError> {
+    let mut efforts = efforts.clone();
+    efforts.rebuild();
+
+    let mut workbook = Workbook::new();
+    let mut used_names: HashSet<String> = HashSet::from([SOVRA_SHEET.to_lowercase()]);
+
+    for prj in efforts.projects.iter() {
+        let name = sheet_name(&prj.text, &mut used_names);
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(name)?;
+        write_project(worksheet, prj)?;
+    }
+
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(SOVRA_SHEET)?;
+    write_sovra(worksheet, &efforts)?;
+
+    Ok(workbook)
+}
+
+/// Exports the plan to an Excel file.
+///
+/// # Arguments
+/// * `efforts` - The plan to export
+/// * `path` - File path of the workbook to create
+///
+/// # Returns
+/// * `Ok(())` - If the file was successfully written
+/// * `Err(std::io::Error)` - If the workbook could not be built or written
+pub fn export_xlsx(efforts: &EffortsDto, path: &str) -> std::io::Result<()> {
+    build_workbook(efforts)
+        .and_then(|mut workbook| workbook.save(path))
+        .map_err(std::io::Error::other)
+}
+
+/// Returns a valid, unique Excel sheet name for a project.
+fn sheet_name(text: &str, used: &mut HashSet<String>) -> String {
+    let mut base: String = text
+        .chars()
+        .map(|c| if "[]:*?/\\".contains(c) { '_' } else { c })
+        .collect::<String>()
+        .trim()
+        .trim_matches('\'')
+        .chars()
+        .take(31)
+        .collect();
+    if base.is_empty() {
+        base = "Project".to_string();
+    }
+
+    let mut name = base.clone();
+    let mut n = 2;
+    while !used.insert(name.to_lowercase()) {
+        let suffix = format!(" ({})", n);
+        name = base
+            .chars()
+            .take(31 - suffix.len())
+            .chain(suffix.chars())
+            .collect();
+        n += 1;
+    }
+    name
+}
+
+fn header_format() -> Format {
+    Format::new()
+        .set_bold()
+        .set_align(FormatAlign::Center)
+        .set_background_color(Color::RGB(0xd9d9d9))
+        .set_border(FormatBorder::Thin)
+}
+
+fn write_header(
+    worksheet: &mut Worksheet,
+    titles: &[&str],
+    weeks: &[i32],
+) -> Result<(), XlsxError> {
+    let header = header_format();
+    for (col, title) in titles.iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *title, &header)?;
+    }
+    for (i, week) in weeks.iter().enumerate() {
+        let col = (titles.len() + i) as u16;
+        worksheet.write_string_with_format(0, col, date_text(*week), &header)?;
+        worksheet.set_column_width(col, 16)?;
+    }
+    worksheet.set_freeze_panes(1, titles.len() as u16)?;
+    Ok(())
+}
+
+fn write_project(worksheet: &mut Worksheet, prj: &EffortByPrjDto) -> Result<(), XlsxError> {
+    let weeks: Vec<i32> = prj
+        .efforts
+        .iter()
+        .flat_map(|d| d.datas.iter().map(|data| data.week))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let week_col: HashMap<i32, u16> = weeks
+        .iter()
+        .enumerate()
+        .map(|(i, w)| (*w, FIRST_WEEK_COL + i as u16))
+        .collect();
+
+    write_header(
+        worksheet,
+        &["Team", "Budget", "Consumed", "Remains"],
+        &weeks,
+    )?;
+    worksheet.set_column_width(0, 12)?;
+
+    let cell = Format::new().set_border(FormatBorder::Thin);
+    let number = cell.clone().set_align(FormatAlign::Center);
+    let alarm = number.clone().set_background_color(Color::RGB(0xff0000));
+
+    let mut row: u32 = 1;
+    for dev in prj.efforts.iter().filter(|d| d.enable) {
+        let persons = dev
+            .datas
+            .iter()
+            .map(|d| d.persons.len())
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let last_row = row + persons as u32;
+
+        let team = Format::new()
+            .set_bold()
+            .set_align(FormatAlign::Center)
+            .set_align(FormatAlign::VerticalCenter)
+            .set_background_color(Color::RGB(dev.dev.color()))
+            .set_font_color(Color::RGB(dev.dev.text_color()))
+            .set_border(FormatBorder::Thin);
+        worksheet.merge_range(row, 0, last_row, 0, dev.dev.title(), &team)?;
+
+        let remains_alarm = (dev.remains == dev.effort && dev.effort != 0) || dev.remains < 0;
+        worksheet.write_number_with_format(row, 1, dev.effort, &number)?;
+        worksheet.write_number_with_format(row, 2, dev.total, &number)?;
+        worksheet.write_number_with_format(
+            row,
+            3,
+            dev.remains,
+            if remains_alarm { &alarm } else { &number },
+        )?;
+
+        for data in dev.datas.iter() {
+            let col = week_col[&data.week];
+            let total = match data.consumption_color() {
+                Some(color) => number.clone().set_bold().set_font_color(Color::RGB(color)),
+                None => number.clone(),
+            };
+            worksheet.write_number_with_format(row, col, data.total, &total)?;
+            for (i, person) in data.persons.iter().enumerate() {
+                if !person.is_empty() {
+                    worksheet.write_string_with_format(row + 1 + i as u32, col, person, &cell)?;
+                }
+            }
+        }
+
+        row = last_row + 1;
+    }
+
+    Ok(())
+}
+
+fn write_sovra(worksheet: &mut Worksheet, efforts: &EffortsDto) -> Result<(), XlsxError> {
+    let weeks: Vec<i32> = efforts.sovra.iter().map(|s| s.week).collect();
+    write_header(worksheet, &["Worker"], &weeks)?;
+    worksheet.set_column_width(0, 24)?;
+
+    let name = Format::new().set_border(FormatBorder::Thin);
+    let first_week = weeks.first().copied().unwrap_or(0);
+    let mut row = 0;
+    for (n, worker) in efforts.worker_names.iter().enumerate() {
+        // As in the footer: no row when retired before the first week
+        if efforts.is_retired(worker, first_week) {
+            continue;
+        }
+        row += 1;
+        worksheet.write_string_with_format(row, 0, worker, &name)?;
+        let load = efforts.load_target(worker);
+        for (i, sovra) in efforts.sovra.iter().enumerate() {
+            if efforts.is_retired(worker, sovra.week) {
+                continue;
+            }
+            let value = sovra.value.get(n).copied().unwrap_or(0);
+            let format = Format::new()
+                .set_align(FormatAlign::Center)
+                .set_border(FormatBorder::Thin)
+                .set_background_color(Color::RGB(LoadLevel::from_load(value, &load).color()));
+            worksheet.write_number_with_format(row, i as u16 + 1, value, &format)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sheet_name_sanitized_and_unique() {
+        let mut used = HashSet::from([SOVRA_SHEET.to_lowercase()]);
+        assert_eq!(sheet_name("A/B: test?", &mut used), "A_B_ test_");
+        assert_eq!(sheet_name("Alpha", &mut used), "Alpha");
+        assert_eq!(sheet_name("alpha", &mut used), "alpha (2)");
+        assert_eq!(sheet_name("Sovra", &mut used), "Sovra (2)");
+        assert_eq!(sheet_name("", &mut used), "Project");
+        let long = "x".repeat(40);
+        assert_eq!(sheet_name(&long, &mut used).chars().count(), 31);
+        assert_eq!(
+            sheet_name(&long, &mut used),
+            format!("{} (2)", "x".repeat(27))
+        );
+    }
+
+    #[test]
+    fn test_build_workbook() {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|50".to_string()];
+        let mut workbook = build_workbook(&efforts).unwrap();
+        let buffer = workbook.save_to_buffer().unwrap();
+        // xlsx files are zip archives
+        assert_eq!(&buffer[0..2], b"PK");
+        assert_eq!(workbook.worksheets().len(), 2);
+    }
+}
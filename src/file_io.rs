@@ -1,11 +1,88 @@
 //! File I/O operations for saving and loading effort data.
 
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::Write;
 
-use crate::models::EffortsDto;
+use crate::models::{CompactError, CompactFile, EffortsDto};
+use crate::workspace::{Workspace, is_workspace_file};
 
-/// Saves efforts data to a JSON file.
+/// Layout of a plan file on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileLayout {
+    /// The full plan as indented JSON.
+    Pretty,
+    /// Only non-empty assignments as sorted one-line records, see [`CompactFile`].
+    Compact,
+}
+
+impl FileLayout {
+    /// Detects the layout of the content of a plan file.
+    pub fn detect(content: &str) -> FileLayout {
+        let head: String = content.chars().filter(|c| !c.is_whitespace()).take(10).collect();
+        if head.starts_with("{\"format\"") {
+            FileLayout::Compact
+        } else {
+            FileLayout::Pretty
+        }
+    }
+
+    /// Layout of an existing file, `None` if it cannot be read.
+    pub fn of_file(path: &str) -> Option<FileLayout> {
+        std::fs::read_to_string(path)
+            .ok()
+            .map(|content| FileLayout::detect(&content))
+    }
+}
+
+/// Whether the path names an SQLite database (`.db`, `.sqlite`, `.sqlite3`).
+///
+/// # Examples
+/// ```
+/// # use project_app::file_io::is_sqlite_path;
+/// assert!(is_sqlite_path("plan.db"));
+/// assert!(!is_sqlite_path("efforts.json"));
+/// ```
+pub fn is_sqlite_path(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ["db", "sqlite", "sqlite3"]
+                .iter()
+                .any(|e| ext.eq_ignore_ascii_case(e))
+        })
+}
+
+#[cfg(feature = "sqlite")]
+fn save_efforts_to_db(efforts: &EffortsDto, path: &str) -> std::io::Result<()> {
+    crate::sqlite::save_efforts_to_db(efforts, path)
+        .map(|count| println!("Saved {} changed rows to \"{}\"", count, path))
+        .map_err(std::io::Error::other)
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn save_efforts_to_db(_efforts: &EffortsDto, _path: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "SQLite support not enabled (feature \"sqlite\")",
+    ))
+}
+
+#[cfg(feature = "sqlite")]
+fn load_efforts_from_db(path: &str) -> Result<EffortsDto, String> {
+    crate::sqlite::load_efforts_from_db(path).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn load_efforts_from_db(_path: &str) -> Result<EffortsDto, String> {
+    Err("SQLite support not enabled (feature \"sqlite\")".to_string())
+}
+
+/// Saves efforts data to a JSON file, or to an SQLite database.
+///
+/// An existing file in compact layout stays compact, otherwise the pretty
+/// layout is used. A database path (see [`is_sqlite_path`]) only gets the
+/// rows that changed, a workspace manifest saves each project to its file.
 ///
 /// # Arguments
 /// * `efforts` - Reference to the EffortsDto to save
@@ -23,15 +100,106 @@ use crate::models::EffortsDto;
 /// save_efforts_to_file(&efforts, "test.json").expect("Failed to save");
 /// ```
 pub fn save_efforts_to_file(efforts: &EffortsDto, path: &str) -> std::io::Result<()> {
-    let json = serde_json::to_string_pretty(efforts).unwrap();
+    if is_sqlite_path(path) {
+        return save_efforts_to_db(efforts, path);
+    }
+    if is_workspace_file(path) {
+        let mut workspace = Workspace::open(path).map_err(std::io::Error::other)?;
+        return workspace
+            .save(efforts)
+            .map(|files| println!("Saved workspace files: {:?}", files));
+    }
+    let layout = FileLayout::of_file(path).unwrap_or(FileLayout::Pretty);
+    save_efforts_with_layout(efforts, path, layout)
+}
+
+/// Saves efforts data to a JSON file in the given layout.
+///
+/// # Arguments
+/// * `efforts` - Reference to the EffortsDto to save
+/// * `path` - File path where data should be saved
+/// * `layout` - Layout of the written file
+pub fn save_efforts_with_layout(
+    efforts: &EffortsDto,
+    path: &str,
+    layout: FileLayout,
+) -> std::io::Result<()> {
+    let mut json = match layout {
+        FileLayout::Pretty => serde_json::to_string_pretty(efforts).unwrap(),
+        FileLayout::Compact => serde_json::to_string_pretty(&CompactFile::from(efforts)).unwrap(),
+    };
+    if layout == FileLayout::Compact {
+        json.push('\n');
+    }
     let mut file = File::create(path)?;
     file.write_all(json.as_bytes())?;
     Ok(())
 }
 
-/// Loads efforts data from a JSON file.
+/// Error of [`parse_efforts`]: the JSON path of the value that is wrong and
+/// why, e.g. `projects[0].efforts[2].dev: unknown variant \`Fw\``.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The content does not match the format (see [`crate::schema`]).
+    Json(serde_path_to_error::Error<serde_json::Error>),
+    /// A compact file whose values do not fit the plan.
+    Compact(CompactError),
+}
+
+impl ParseError {
+    /// JSON path of the wrong value.
+    pub fn path(&self) -> String {
+        match self {
+            ParseError::Json(e) => e.path().to_string(),
+            ParseError::Compact(e) => e.path.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Json(e) => e.fmt(f),
+            ParseError::Compact(e) => e.fmt(f),
+        }
+    }
+}
+
+/// Parses the content of a plan file in either layout.
+pub fn parse_efforts(content: &str) -> Result<EffortsDto, ParseError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(content);
+    match FileLayout::detect(content) {
+        FileLayout::Pretty => {
+            serde_path_to_error::deserialize(deserializer).map_err(ParseError::Json)
+        }
+        FileLayout::Compact => serde_path_to_error::deserialize::<_, CompactFile>(deserializer)
+            .map_err(ParseError::Json)
+            .and_then(|file| EffortsDto::try_from(file).map_err(ParseError::Compact)),
+    }
+}
+
+/// Reads a plan from a JSON file, in pretty or compact layout, from an SQLite
+/// database or from all the files of a workspace.
+///
+/// # Returns
+/// * `Ok(EffortsDto)` - The loaded plan
+/// * `Err(String)` - Why the file cannot be read; for JSON files, where the
+///   content does not match the format
+pub fn read_efforts_file(path: &str) -> Result<EffortsDto, String> {
+    if is_sqlite_path(path) {
+        return load_efforts_from_db(path);
+    }
+    if is_workspace_file(path) {
+        return Workspace::open(path).and_then(|workspace| workspace.load());
+    }
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_efforts(&content).map_err(|e| e.to_string())
+}
+
+/// Loads efforts data from a JSON file, in pretty or compact layout, from an
+/// SQLite database or from all the files of a workspace.
 ///
-/// If the file doesn't exist or contains invalid JSON, returns a default EffortsDto.
+/// If the file doesn't exist or cannot be read, returns a default EffortsDto.
 ///
 /// # Arguments
 /// * `path` - File path to load data from
@@ -45,26 +213,31 @@ pub fn save_efforts_to_file(efforts: &EffortsDto, path: &str) -> std::io::Result
 /// let efforts = load_efforts_from_file("efforts.json");
 /// ```
 pub fn load_efforts_from_file(path: &str) -> EffortsDto {
-    let json_str = std::fs::read_to_string(path);
-    if let Ok(json_str) = json_str {
-        let efforts_dto: Result<EffortsDto, serde_json::Error> = serde_json::from_str(&json_str);
-        if let Ok(efforts_dto) = efforts_dto {
-            return efforts_dto;
-        } else {
-            println!(
-                "{}",
-                efforts_dto
-                    .expect_err(format!("Error during parse the file \"{}\"", path).as_str())
-            );
-        }
-    } else {
-        println!(
-            "{}",
-            json_str.expect_err(format!("Error during load the file \"{}\"", path).as_str())
-        );
+    read_efforts_file(path).unwrap_or_else(|e| {
+        println!("Error during load the file \"{}\": {}", path, e);
+        println!("Create a default EffortsDto");
+        EffortsDto::default()
+    })
+}
+
+/// Checks whether a file can be opened for writing.
+///
+/// Returns `false` when the file exists but is read-only or locked by another
+/// process. A missing file is considered writable, since saving will create it.
+///
+/// # Arguments
+/// * `path` - File path to check
+///
+/// # Examples
+/// ```
+/// # use project_app::file_io::is_file_writable;
+/// assert!(is_file_writable("nonexistent_file_12345.json"));
+/// ```
+pub fn is_file_writable(path: &str) -> bool {
+    if !std::path::Path::new(path).exists() {
+        return true;
     }
-    println!("Create a default EffortsDto");
-    EffortsDto::default()
+    OpenOptions::new().write(true).open(path).is_ok()
 }
 
 #[cfg(test)]
@@ -113,4 +286,38 @@ mod tests {
         // Cleanup
         let _ = fs::remove_file(test_file);
     }
+
+    #[test]
+    fn test_compact_layout_is_kept() {
+        let test_file = "test_compact_temp.json";
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|50".to_string()];
+        efforts.rebuild();
+
+        save_efforts_with_layout(&efforts, test_file, FileLayout::Compact).expect("Failed to save");
+        assert_eq!(FileLayout::of_file(test_file), Some(FileLayout::Compact));
+        let first = fs::read_to_string(test_file).unwrap();
+
+        let loaded = load_efforts_from_file(test_file);
+        assert_eq!(loaded.projects[0].efforts[0].datas[0].persons[0], "Mario|50");
+
+        // Saving again keeps the layout and the bytes
+        save_efforts_to_file(&loaded, test_file).expect("Failed to save");
+        assert_eq!(fs::read_to_string(test_file).unwrap(), first);
+
+        // Cleanup
+        let _ = fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_is_file_writable() {
+        let test_file = "test_writable_temp.json";
+        assert!(is_file_writable(test_file));
+
+        save_efforts_to_file(&EffortsDto::default(), test_file).expect("Failed to save");
+        assert!(is_file_writable(test_file));
+
+        // Cleanup
+        let _ = fs::remove_file(test_file);
+    }
 }
@@ -0,0 +1,390 @@
+//! CSV import of worker assignments.
+//!
+//! Each line holds `project, team, worker, week start date, percent`. A header line
+//! is optional (recognized by its `project`/`progetto` and `percent`/`percentuale`
+//! columns), and extra columns are ignored, so a CSV produced by the list export
+//! can be imported back.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Read;
+
+use chrono::NaiveDate;
+
+use crate::date_utils::{local_to_days, primo_giorno_settimana_corrente};
+use crate::models::{Devs, EffortByPrjDto, EffortsDto};
+use crate::utils::info_cell;
+
+/// How imported assignments are combined with the existing cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Adds or updates the imported workers, keeping the others in the cell.
+    /// A percent of 0 removes the worker from the cell.
+    Merge,
+    /// Every (project, team, week) cell mentioned in the CSV is emptied first.
+    Replace,
+}
+
+/// A problem found on a single CSV line. The line is skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "riga {}: {}", self.line, self.message)
+    }
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Number of assignments written into the plan.
+    pub imported: usize,
+    /// Names of the projects created by the import.
+    pub created_projects: Vec<String>,
+    pub errors: Vec<ImportError>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Assegnazioni importate: {}", self.imported)?;
+        for name in self.created_projects.iter() {
+            writeln!(f, "Progetto creato: {}", name)?;
+        }
+        for error in self.errors.iter() {
+            writeln!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// One assignment to write into the plan.
+pub(crate) struct Row {
+    pub project: String,
+    pub dev: Devs,
+    pub worker: String,
+    pub week: i32,
+    pub percent: i32,
+}
+
+const DATE_FORMATS: [&str; 3] = ["%Y-%m-%d", "%d/%m/%Y", "%d-%m-%Y"];
+
+fn parse_week(text: &str) -> Option<i32> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(text, f).ok())
+        .map(|date| local_to_days(&primo_giorno_settimana_corrente(&date)))
+}
+
+fn parse_percent(text: &str) -> Option<i32> {
+    text.trim_end_matches('%')
+        .trim()
+        .parse::<i32>()
+        .ok()
+        .filter(|p| *p >= 0)
+}
+
+fn parse_row(fields: &[&str]) -> Result<Row, String> {
+    if fields.len() < 5 {
+        return Err(format!("attese 5 colonne, trovate {}", fields.len()));
+    }
+    let project = fields[0];
+    if project.is_empty() {
+        return Err("nome progetto mancante".to_string());
+    }
+    let dev = Devs::from_title(fields[1]).ok_or(format!("team sconosciuto \"{}\"", fields[1]))?;
+    let worker = fields[2];
+    if worker.is_empty() || worker.contains('|') {
+        return Err(format!("worker non valido \"{}\"", worker));
+    }
+    let week = parse_week(fields[3]).ok_or(format!(
+        "data non valida \"{}\" (formato atteso: YYYY-MM-DD o DD/MM/YYYY)",
+        fields[3]
+    ))?;
+    let percent =
+        parse_percent(fields[4]).ok_or(format!("percentuale non valida \"{}\"", fields[4]))?;
+
+    Ok(Row {
+        project: project.to_string(),
+        dev,
+        worker: worker.to_string(),
+        week,
+        percent,
+    })
+}
+
+/// Writes `worker|percent` into a cell, reusing the worker's slot or the first empty one.
+fn place(persons: &mut Vec<String>, worker: &str, percent: i32) {
+    let value = if percent == 0 {
+        String::new()
+    } else {
+        format!("{}|{}", worker, percent)
+    };
+
+    if let Some(slot) = persons
+        .iter_mut()
+        .find(|p| info_cell(p).is_some_and(|(name, _)| name == worker))
+    {
+        *slot = value;
+    } else if percent != 0 {
+        if let Some(slot) = persons.iter_mut().find(|p| p.trim().is_empty()) {
+            *slot = value;
+        } else {
+            persons.push(value);
+        }
+    }
+}
+
+/// Returns the index of the project with the given name, creating it when missing.
+fn project_index(efforts: &mut EffortsDto, name: &str, report: &mut ImportReport) -> usize {
+    if let Some(index) = efforts.projects.iter().position(|p| p.text.trim() == name) {
+        return index;
+    }
+    let mut prj = EffortByPrjDto::new(efforts.projects.len() as i32);
+    prj.text = name.to_string();
+    efforts.projects.push(prj);
+    report.created_projects.push(name.to_string());
+    efforts.projects.len() - 1
+}
+
+/// Whether the line is the header, `project, ..., percent` in English or Italian.
+fn is_header(fields: &[&str]) -> bool {
+    let is = |index: usize, names: &[&str]| {
+        fields
+            .get(index)
+            .is_some_and(|f| names.iter().any(|n| f.eq_ignore_ascii_case(n)))
+    };
+    is(0, &["project", "progetto"]) && is(4, &["percent", "percentuale"])
+}
+
+/// Imports assignments from CSV text into the plan.
+///
+/// Lines with errors are reported and skipped, the others are applied. Afterwards
+/// totals, worker list and over-allocation are recomputed with [`EffortsDto::rebuild`].
+///
+/// # Arguments
+/// * `efforts` - The plan to update
+/// * `reader` - Source of the CSV text
+/// * `mode` - Merge into or replace the affected cells
+/// * `separator` - Field separator
+pub fn import_csv<R: Read>(
+    efforts: &mut EffortsDto,
+    reader: R,
+    mode: ImportMode,
+    separator: u8,
+) -> ImportReport {
+    let mut report = ImportReport::default();
+    let mut rdr = ::csv::ReaderBuilder::new()
+        .delimiter(separator)
+        .has_headers(false)
+        .flexible(true)
+        .trim(::csv::Trim::All)
+        .from_reader(reader);
+
+    let mut rows = vec![];
+    for (index, record) in rdr.records().enumerate() {
+        let line = record
+            .as_ref()
+            .ok()
+            .and_then(|r| r.position())
+            .map_or(index as u64 + 1, |p| p.line());
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                report.errors.push(ImportError {
+                    line,
+                    message: e.to_string(),
+                });
+                continue;
+            }
+        };
+        let fields: Vec<&str> = record.iter().collect();
+        if fields.iter().all(|f| f.is_empty()) {
+            continue;
+        }
+        // Optional header line
+        if index == 0 && is_header(&fields) {
+            continue;
+        }
+        match parse_row(&fields) {
+            Ok(row) => rows.push(row),
+            Err(message) => report.errors.push(ImportError { line, message }),
+        }
+    }
+
+    apply_rows(efforts, rows, mode, &mut report);
+    report
+}
+
+/// Writes assignment rows into the plan, creating the missing projects, then
+/// recomputes totals, worker list and over-allocation.
+pub(crate) fn apply_rows(
+    efforts: &mut EffortsDto,
+    rows: Vec<Row>,
+    mode: ImportMode,
+    report: &mut ImportReport,
+) {
+    let mut cleared = HashSet::new();
+    for row in rows.into_iter() {
+        let index = project_index(efforts, &row.project, report);
+        let prj = &mut efforts.projects[index];
+        prj.set_date(prj.start_week.min(row.week), prj.end_week.max(row.week + 7));
+
+        let Some(dev) = prj.efforts.iter_mut().find(|d| d.dev == row.dev) else {
+            continue;
+        };
+        dev.enable = true;
+        let Some(data) = dev.datas.iter_mut().find(|d| d.week == row.week) else {
+            continue;
+        };
+
+        if mode == ImportMode::Replace && cleared.insert((index, row.dev, row.week)) {
+            for person in data.persons.iter_mut() {
+                person.clear();
+            }
+        }
+        place(&mut data.persons, &row.worker, row.percent);
+        report.imported += 1;
+    }
+
+    efforts.rebuild();
+}
+
+/// Guesses the field separator from the first line of a CSV text.
+///
+/// # Examples
+/// ```
+/// # use project_app::importers::csv::detect_separator;
+/// assert_eq!(detect_separator("a;b;c,5"), b';');
+/// assert_eq!(detect_separator("a,b,c"), b',');
+/// ```
+pub fn detect_separator(text: &str) -> u8 {
+    let first_line = text.lines().next().unwrap_or_default();
+    // On ties `max_by_key` keeps the last candidate, so ',' goes last
+    [b'\t', b';', b',']
+        .into_iter()
+        .max_by_key(|sep| first_line.bytes().filter(|b| b == sep).count())
+        .unwrap_or(b',')
+}
+
+/// Imports assignments from a CSV file into the plan.
+///
+/// When `separator` is `None`, it is detected from the first line of the file.
+///
+/// # Returns
+/// * `Ok(ImportReport)` - The outcome of the import, including per-line errors
+/// * `Err(std::io::Error)` - If the file could not be opened
+pub fn import_csv_file(
+    efforts: &mut EffortsDto,
+    path: &str,
+    mode: ImportMode,
+    separator: Option<u8>,
+) -> std::io::Result<ImportReport> {
+    let text = std::fs::read_to_string(path)?;
+    let separator = separator.unwrap_or_else(|| detect_separator(&text));
+    Ok(import_csv(efforts, text.as_bytes(), mode, separator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Lucia|30".to_string()];
+        efforts
+    }
+
+    fn week_text(efforts: &EffortsDto, index: usize) -> String {
+        crate::date_utils::days_to_local(efforts.projects[0].efforts[0].datas[index].week)
+            .format("%Y-%m-%d")
+            .to_string()
+    }
+
+    #[test]
+    fn test_merge_into_existing_cell() {
+        let mut efforts = sample();
+        let csv = format!(
+            "project,team,worker,week,percent\nAlpha,Mcsw,Mario,{},50\n",
+            week_text(&efforts, 0)
+        );
+        let report = import_csv(&mut efforts, csv.as_bytes(), ImportMode::Merge, b',');
+        assert!(report.errors.is_empty());
+        assert_eq!(report.imported, 1);
+        assert_eq!(
+            efforts.projects[0].efforts[0].datas[0].persons,
+            vec!["Lucia|30", "Mario|50"]
+        );
+        // Recalculated like the GUI does
+        assert_eq!(efforts.projects[0].efforts[0].total, 32);
+        assert!(efforts.worker_names.contains(&"Mario".to_string()));
+    }
+
+    #[test]
+    fn test_replace_cell() {
+        let mut efforts = sample();
+        let csv = format!("Alpha;Mcsw;Mario;{};50\n", week_text(&efforts, 0));
+        let report = import_csv(&mut efforts, csv.as_bytes(), ImportMode::Replace, b';');
+        assert!(report.errors.is_empty());
+        assert_eq!(
+            efforts.projects[0].efforts[0].datas[0].persons,
+            vec!["Mario|50"]
+        );
+    }
+
+    #[test]
+    fn test_creates_missing_project() {
+        let mut efforts = sample();
+        let csv = format!("Beta,Hw Test,Anna,{},100\n", week_text(&efforts, 2));
+        let report = import_csv(&mut efforts, csv.as_bytes(), ImportMode::Merge, b',');
+        assert_eq!(report.created_projects, vec!["Beta"]);
+        let prj = &efforts.projects[1];
+        assert_eq!(prj.project, 1);
+        let dev = prj.efforts.iter().find(|d| d.dev == Devs::TestHw).unwrap();
+        assert!(dev.datas.iter().any(|d| d.persons == vec!["Anna|100"]));
+    }
+
+    #[test]
+    fn test_errors_per_line() {
+        let mut efforts = sample();
+        let week = week_text(&efforts, 0);
+        let csv = format!(
+            "Alpha,Nope,Mario,{week},50\nAlpha,Mcsw,Mario,31/02/2025,50\nAlpha,Mcsw,Mario,{week},abc\nAlpha,Mcsw,Mario,{week},10\n"
+        );
+        let report = import_csv(&mut efforts, csv.as_bytes(), ImportMode::Merge, b',');
+        assert_eq!(report.imported, 1);
+        let lines: Vec<u64> = report.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 2, 3]);
+        assert!(report.errors[0].message.contains("Nope"));
+    }
+
+    #[test]
+    fn test_header_by_column_names() {
+        let mut efforts = sample();
+        let week = week_text(&efforts, 0);
+        let csv =
+            format!("Progetto;Team;Worker;Settimana;Percentuale\nAlpha;Mcsw;Mario;{week};50\n");
+        let report = import_csv(&mut efforts, csv.as_bytes(), ImportMode::Merge, b';');
+        assert!(report.errors.is_empty());
+        assert_eq!(report.imported, 1);
+
+        // Without a header a bad first line is an error, not a skipped header
+        let csv = format!("Alpha,Mcsw,Anna,{week},5O\nAlpha,Mcsw,Anna,{week},10\n");
+        let report = import_csv(&mut efforts, csv.as_bytes(), ImportMode::Merge, b',');
+        assert_eq!(report.imported, 1);
+        let lines: Vec<u64> = report.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1]);
+    }
+
+    #[test]
+    fn test_zero_percent_removes_worker() {
+        let mut efforts = sample();
+        let csv = format!("Alpha,Mcsw,Lucia,{},0\n", week_text(&efforts, 0));
+        import_csv(&mut efforts, csv.as_bytes(), ImportMode::Merge, b',');
+        assert_eq!(efforts.projects[0].efforts[0].datas[0].persons, vec![""]);
+    }
+}
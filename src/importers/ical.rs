@@ -0,0 +1,396 @@
+//! iCalendar (.ics) import of holidays and company closures.
+//!
+//! Only all-day events (`DTSTART;VALUE=DATE:YYYYMMDD`) are imported: every day
+//! from `DTSTART` up to the exclusive `DTEND` becomes a holiday. Yearly recurring
+//! events (`RRULE:FREQ=YEARLY`, with optional `INTERVAL`, `COUNT` and `UNTIL`) are
+//! repeated over the years covered by the plan; other rules are reported.
+
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate};
+
+use super::csv::ImportError;
+use crate::date_utils::local_to_days;
+use crate::models::{EffortsDto, HolidayDto};
+
+/// Outcome of a holiday import.
+#[derive(Debug, Clone, Default)]
+pub struct HolidayImportReport {
+    /// Number of holiday days added to the plan.
+    pub imported: usize,
+    /// Events that could not be imported. `line` is where the event begins.
+    pub skipped: Vec<ImportError>,
+}
+
+impl fmt::Display for HolidayImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Giorni di festività importati: {}", self.imported)?;
+        for error in self.skipped.iter() {
+            writeln!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// An all-day event read from the calendar.
+#[derive(Debug, Clone, PartialEq)]
+struct Event {
+    name: String,
+    start: NaiveDate,
+    /// Exclusive.
+    end: NaiveDate,
+    yearly: Option<Yearly>,
+}
+
+/// A `FREQ=YEARLY` recurrence rule.
+#[derive(Debug, Clone, PartialEq)]
+struct Yearly {
+    interval: i32,
+    count: Option<i32>,
+    /// Inclusive, last day an occurrence can start.
+    until: Option<NaiveDate>,
+}
+
+impl Yearly {
+    /// Parses the value of `RRULE`, e.g. `FREQ=YEARLY;COUNT=2`.
+    fn parse(rule: &str, start: NaiveDate) -> Result<Yearly, String> {
+        let unsupported = || format!("ricorrenza non supportata \"{}\"", rule);
+        let mut yearly = false;
+        let mut ret = Yearly {
+            interval: 1,
+            count: None,
+            until: None,
+        };
+        for part in rule.split(';').filter(|p| !p.is_empty()) {
+            let (key, value) = part.split_once('=').ok_or_else(unsupported)?;
+            let number = || value.parse::<i32>().ok().filter(|n| *n > 0);
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => yearly = value.eq_ignore_ascii_case("YEARLY"),
+                "INTERVAL" => ret.interval = number().ok_or_else(unsupported)?,
+                "COUNT" => ret.count = Some(number().ok_or_else(unsupported)?),
+                // A date or a date-time, whose day is enough here
+                "UNTIL" => {
+                    ret.until = Some(
+                        value
+                            .get(..8)
+                            .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+                            .ok_or_else(unsupported)?,
+                    )
+                }
+                // Redundant when they name the day of DTSTART
+                "BYMONTH" if value == start.month().to_string() => {}
+                "BYMONTHDAY" if value == start.day().to_string() => {}
+                "WKST" => {}
+                _ => return Err(unsupported()),
+            }
+        }
+        if yearly { Ok(ret) } else { Err(unsupported()) }
+    }
+
+    /// Start of each occurrence from `start`, in the years `first..=last`.
+    fn occurrences(&self, start: NaiveDate, first: i32, last: i32) -> Vec<NaiveDate> {
+        (0..)
+            .map(|n| (n, start.year() + n * self.interval))
+            .take_while(|(n, year)| *year <= last && self.count.is_none_or(|c| *n < c))
+            .filter(|(_, year)| *year >= first)
+            .filter_map(|(_, year)| start.with_year(year))
+            .filter(|date| self.until.is_none_or(|until| *date <= until))
+            .collect()
+    }
+}
+
+/// A content line of an event: name, parameters and value.
+type Property = (String, String, String);
+
+/// Joins folded lines (continuations start with a space or a tab).
+fn unfold(text: &str) -> Vec<(u64, String)> {
+    let mut lines: Vec<(u64, String)> = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ => lines.push((index as u64 + 1, line.to_string())),
+        }
+    }
+    lines
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push(' '),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Parses a `DTSTART`/`DTEND` property. Returns `None` for date-time values.
+fn parse_date(params: &str, value: &str) -> Option<NaiveDate> {
+    let all_day = params.to_ascii_uppercase().contains("VALUE=DATE") || value.len() == 8;
+    if !all_day || value.len() != 8 {
+        return None;
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d").ok()
+}
+
+fn parse_event(props: &[Property]) -> Result<Event, String> {
+    let mut name = String::new();
+    let mut start = None;
+    let mut end = None;
+    let mut rule = None;
+
+    for (key, params, value) in props.iter() {
+        match key.as_str() {
+            "SUMMARY" => name = unescape(value),
+            "DTSTART" => start = Some((params, value)),
+            "DTEND" => end = parse_date(params, value),
+            "RRULE" => rule = Some(value),
+            _ => {}
+        }
+    }
+
+    // Checked once SUMMARY is known, wherever it is in the event
+    let (params, value) = start.ok_or(format!("evento \"{}\": DTSTART mancante", name))?;
+    let start = parse_date(params, value).ok_or(format!(
+        "evento \"{}\" non di un giorno intero ({})",
+        name, value
+    ))?;
+    let yearly = rule
+        .map(|rule| Yearly::parse(rule, start))
+        .transpose()
+        .map_err(|e| format!("evento \"{}\": {}", name, e))?;
+    let end = end
+        .filter(|e| *e > start)
+        .unwrap_or(start + chrono::Duration::days(1));
+    Ok(Event {
+        name,
+        start,
+        end,
+        yearly,
+    })
+}
+
+/// Reads the all-day events of a calendar. Other events are reported.
+fn parse_events(text: &str) -> (Vec<Event>, Vec<ImportError>) {
+    let mut events = vec![];
+    let mut errors = vec![];
+    let mut current: Option<(u64, Vec<Property>)> = None;
+
+    for (line, content) in unfold(text) {
+        let Some((head, value)) = content.split_once(':') else {
+            continue;
+        };
+        let (key, params) = head.split_once(';').unwrap_or((head, ""));
+        let key = key.to_ascii_uppercase();
+
+        match (key.as_str(), value.trim().to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => current = Some((line, vec![])),
+            ("END", "VEVENT") => {
+                if let Some((begin, props)) = current.take() {
+                    match parse_event(&props) {
+                        Ok(event) => events.push(event),
+                        Err(message) => errors.push(ImportError {
+                            line: begin,
+                            message,
+                        }),
+                    }
+                }
+            }
+            _ => {
+                if let Some((_, props)) = current.as_mut() {
+                    props.push((key, params.to_string(), value.trim().to_string()));
+                }
+            }
+        }
+    }
+
+    (events, errors)
+}
+
+/// Imports the all-day events of a calendar as holidays of the plan.
+///
+/// Days already present for the same workers are not duplicated. Afterwards the
+/// over-allocation is recomputed with [`EffortsDto::rebuild`].
+///
+/// # Arguments
+/// * `efforts` - The plan to update
+/// * `text` - Content of the .ics file
+/// * `workers` - Workers affected by the holidays; empty means everyone
+pub fn import_ics_holidays(
+    efforts: &mut EffortsDto,
+    text: &str,
+    workers: &[String],
+) -> HolidayImportReport {
+    let (events, skipped) = parse_events(text);
+    let mut report = HolidayImportReport {
+        imported: 0,
+        skipped,
+    };
+
+    let (start_week, end_week) = efforts.start_end_weeks();
+    let first_year = crate::date_utils::days_to_local(start_week).year();
+    let last_year = crate::date_utils::days_to_local(end_week).year();
+
+    let mut workers = workers.to_vec();
+    workers.sort();
+    workers.dedup();
+
+    for event in events.iter() {
+        let mut occurrences = vec![(event.start, event.end)];
+        if let Some(yearly) = event.yearly.as_ref() {
+            occurrences = yearly
+                .occurrences(event.start, first_year, last_year)
+                .into_iter()
+                .map(|start| (start, start + (event.end - event.start)))
+                .collect();
+        }
+
+        for (start, end) in occurrences {
+            for date in start.iter_days().take_while(|d| *d < end) {
+                let holiday = HolidayDto {
+                    day: local_to_days(&date),
+                    name: event.name.clone(),
+                    workers: workers.clone(),
+                };
+                let exists = efforts
+                    .holidays
+                    .iter()
+                    .any(|h| h.day == holiday.day && h.workers == holiday.workers);
+                if !exists {
+                    efforts.holidays.push(holiday);
+                    report.imported += 1;
+                }
+            }
+        }
+    }
+
+    efforts.holidays.sort_by_key(|h| h.day);
+    efforts.rebuild();
+    report
+}
+
+/// Imports the all-day events of an .ics file as holidays of the plan.
+///
+/// # Returns
+/// * `Ok(HolidayImportReport)` - The outcome of the import, including skipped events
+/// * `Err(std::io::Error)` - If the file could not be read
+pub fn import_ics_file(
+    efforts: &mut EffortsDto,
+    path: &str,
+    workers: &[String],
+) -> std::io::Result<HolidayImportReport> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(import_ics_holidays(efforts, &text, workers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(events: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n",
+            events
+        )
+    }
+
+    #[test]
+    fn test_all_day_events() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nSUMMARY:Chiusura\\, estate\r\nDTSTART;VALUE=DATE:20250811\r\nDTEND;VALUE=DATE:20250814\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Riunione\r\nDTSTART:20250812T090000Z\r\nEND:VEVENT\r\n",
+        );
+        let mut efforts = EffortsDto::default();
+        let report = import_ics_holidays(&mut efforts, &text, &[]);
+        assert_eq!(report.imported, 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].line, 8);
+        assert_eq!(efforts.holidays[0].name, "Chiusura, estate");
+        assert_eq!(
+            efforts.holidays[0].day,
+            local_to_days(&NaiveDate::from_ymd_opt(2025, 8, 11).unwrap())
+        );
+
+        // Importing again adds nothing
+        let report = import_ics_holidays(&mut efforts, &text, &[]);
+        assert_eq!(report.imported, 0);
+    }
+
+    #[test]
+    fn test_yearly_event_for_listed_workers() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nSUMMARY:Santo\r\n Patrono\r\nDTSTART;VALUE=DATE:20200624\r\nRRULE:FREQ=YEARLY\r\nEND:VEVENT\r\n",
+        );
+        let mut efforts = EffortsDto::default();
+        let (start, end) = efforts.start_end_weeks();
+        let report = import_ics_holidays(&mut efforts, &text, &["Mario".to_string()]);
+        let years = crate::date_utils::days_to_local(end).year()
+            - crate::date_utils::days_to_local(start).year()
+            + 1;
+        assert_eq!(report.imported as i32, years);
+        assert!(efforts.holidays.iter().all(|h| h.name == "SantoPatrono"));
+        assert!(efforts.holidays[0].applies_to("Mario"));
+        assert!(!efforts.holidays[0].applies_to("Lucia"));
+    }
+
+    #[test]
+    fn test_yearly_rule_limits() {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let years = |rule: &str| -> Vec<i32> {
+            Yearly::parse(rule, start)
+                .unwrap()
+                .occurrences(start, 2020, 2030)
+                .iter()
+                .map(|d| d.year())
+                .collect()
+        };
+        assert_eq!(years("FREQ=YEARLY;COUNT=2"), vec![2024, 2025]);
+        assert_eq!(years("FREQ=YEARLY;UNTIL=20260304"), vec![2024, 2025, 2026]);
+        assert_eq!(
+            years("FREQ=YEARLY;UNTIL=20260303T000000Z"),
+            vec![2024, 2025]
+        );
+        assert_eq!(
+            years("FREQ=YEARLY;INTERVAL=3;COUNT=3"),
+            vec![2024, 2027, 2030]
+        );
+        assert_eq!(
+            years("FREQ=YEARLY;BYMONTH=3"),
+            (2024..=2030).collect::<Vec<_>>()
+        );
+        assert!(Yearly::parse("FREQ=WEEKLY;COUNT=2", start).is_err());
+        assert!(Yearly::parse("FREQ=YEARLY;BYDAY=MO", start).is_err());
+
+        // Reported with the name even when SUMMARY comes after the rule
+        let text = calendar(
+            "BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240304\r\nRRULE:FREQ=MONTHLY\r\nSUMMARY:Ferie\r\nEND:VEVENT\r\n",
+        );
+        let report = import_ics_holidays(&mut EffortsDto::default(), &text, &[]);
+        assert_eq!(report.imported, 0);
+        assert!(report.skipped[0].message.starts_with("evento \"Ferie\""));
+    }
+
+    #[test]
+    fn test_holidays_reduce_capacity() {
+        let mut efforts = EffortsDto::default();
+        let week = efforts.projects[0].efforts[0].datas[0].week;
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|80".to_string()];
+        let monday = crate::date_utils::days_to_local(week).format("%Y%m%d");
+        let text = calendar(&format!(
+            "BEGIN:VEVENT\r\nSUMMARY:Festa\r\nDTSTART;VALUE=DATE:{}\r\nEND:VEVENT\r\n",
+            monday
+        ));
+        import_ics_holidays(&mut efforts, &text, &[]);
+        let sovra = efforts.sovra.iter().find(|s| s.week == week).unwrap();
+        // 80% of a four-day week
+        assert_eq!(sovra.value[0], 100);
+    }
+}
@@ -0,0 +1,20 @@
+//! Imports of data produced by other tools into an effort plan.
+//!
+//! Every importer works on an [`EffortsDto`](crate::models::EffortsDto) and reports
+//! the problems it found instead of stopping at the first one.
+
+pub mod csv;
+pub mod ical;
+pub mod msproject;
+pub mod projects;
+
+// Re-export commonly used items
+pub use csv::{
+    ImportError, ImportMode, ImportReport, detect_separator, import_csv, import_csv_file,
+};
+pub use ical::{HolidayImportReport, import_ics_file, import_ics_holidays};
+pub use msproject::{
+    MsProjectImportReport, MsProjectMapping, import_msproject, import_msproject_file, load_mapping,
+    mapping_for,
+};
+pub use projects::{ProjectImportReport, ProjectSelection, import_projects};
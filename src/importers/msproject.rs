@@ -0,0 +1,505 @@
+//! Import of tasks, resources and assignments from an MS Project XML export.
+//!
+//! Resources become workers, and each task is assigned to one of our projects
+//! and teams through a [`MsProjectMapping`]. The work of every assignment is
+//! spread over its working days (using the time-phased data when present) and
+//! converted into weekly percentages of [`HOURS_PER_WEEK`].
+//!
+//! Whatever cannot be mapped is listed in the report instead of being imported.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+use super::csv::{ImportMode, ImportReport, Row, apply_rows};
+use crate::date_utils::{local_to_days, primo_giorno_settimana_corrente};
+use crate::models::{Devs, EffortsDto};
+use crate::utils::HOURS_PER_WEEK;
+
+/// How MS Project tasks and resources map onto the plan.
+///
+/// Task names are matched against the task itself and then its summary tasks,
+/// nearest first.
+///
+/// ```json
+/// {
+///   "projects": { "Scheda controllo": "Alpha" },
+///   "teams": { "Layout PCB": "Ele", "Collaudo": "Hw Test" },
+///   "workers": { "M. Rossi": "Mario Rossi" },
+///   "team": "Hw"
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct MsProjectMapping {
+    /// Task name -> project name.
+    #[serde(default)]
+    pub projects: BTreeMap<String, String>,
+    /// Task name or resource group -> team, as in the program (`Hw Test`) or in the JSON (`TestHw`).
+    #[serde(default)]
+    pub teams: BTreeMap<String, String>,
+    /// Resource name -> worker name; unlisted resources keep their name.
+    #[serde(default)]
+    pub workers: BTreeMap<String, String>,
+    /// Project of the unmapped tasks; by default the title of the MS Project file.
+    #[serde(default)]
+    pub project: Option<String>,
+    /// Team of the unmapped tasks. A resource group named as a team is used first.
+    #[serde(default)]
+    pub team: Option<String>,
+}
+
+/// Outcome of [`import_msproject`].
+#[derive(Debug, Clone, Default)]
+pub struct MsProjectImportReport {
+    /// Weekly assignments written into the plan.
+    pub assignments: ImportReport,
+    /// Tasks, resources and assignments that could not be mapped.
+    pub unmapped: Vec<String>,
+}
+
+impl fmt::Display for MsProjectImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.assignments)?;
+        if !self.unmapped.is_empty() {
+            writeln!(f, "Non importati:")?;
+            for item in self.unmapped.iter() {
+                writeln!(f, "  {}", item)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Task {
+    /// The task and its summary tasks, nearest first.
+    names: Vec<String>,
+}
+
+struct Resource {
+    name: String,
+    group: String,
+    is_work: bool,
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> &'a str {
+    node.children()
+        .find(|n| n.tag_name().name() == name)
+        .and_then(|n| n.text())
+        .unwrap_or("")
+        .trim()
+}
+
+fn children<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = roxmltree::Node<'a, 'input>> {
+    node.children().filter(move |n| n.tag_name().name() == name)
+}
+
+/// Hours of an ISO 8601 duration as written by MS Project, e.g. `PT7H30M0S`.
+fn parse_hours(text: &str) -> Option<f64> {
+    let text = text.strip_prefix('P')?;
+    let (days, time) = text.split_once('T').unwrap_or((text, ""));
+    let mut hours = 0.0;
+    if let Some(d) = days.strip_suffix('D') {
+        hours += d.parse::<f64>().ok()? * 24.0;
+    }
+    let mut number = String::new();
+    for c in time.chars() {
+        match c {
+            'H' | 'M' | 'S' => {
+                let value = number.parse::<f64>().ok()?;
+                hours += match c {
+                    'H' => value,
+                    'M' => value / 60.0,
+                    _ => value / 3600.0,
+                };
+                number.clear();
+            }
+            c => number.push(c),
+        }
+    }
+    Some(hours)
+}
+
+fn parse_datetime(text: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S").ok()
+}
+
+/// Spreads `hours` over the working days in `[start, finish)` and sums them
+/// per week (Monday, in days). A day counts when the interval covers its midday.
+fn spread(start: NaiveDateTime, finish: NaiveDateTime, hours: f64, weeks: &mut BTreeMap<i32, f64>) {
+    let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+    let mut days = vec![];
+    let mut day = start.date();
+    while day <= finish.date() {
+        let midday = day.and_time(noon);
+        let weekend = matches!(day.weekday(), Weekday::Sat | Weekday::Sun);
+        if !weekend && midday >= start && midday < finish {
+            days.push(day);
+        }
+        day += Duration::days(1);
+    }
+    if days.is_empty() {
+        days.push(start.date());
+    }
+
+    let per_day = hours / days.len() as f64;
+    for day in days.into_iter() {
+        *weeks.entry(week_of(day)).or_insert(0.0) += per_day;
+    }
+}
+
+fn week_of(day: NaiveDate) -> i32 {
+    local_to_days(&primo_giorno_settimana_corrente(&day))
+}
+
+/// Imports an MS Project XML export into the plan.
+///
+/// # Arguments
+/// * `efforts` - The plan to update
+/// * `xml` - Content of the XML file
+/// * `mapping` - How tasks and resources map onto projects, teams and workers
+/// * `mode` - Merge into or replace the affected cells
+///
+/// # Returns
+/// * `Ok(MsProjectImportReport)` - The outcome, with what could not be mapped
+/// * `Err(String)` - If the text is not an MS Project XML file
+pub fn import_msproject(
+    efforts: &mut EffortsDto,
+    xml: &str,
+    mapping: &MsProjectMapping,
+    mode: ImportMode,
+) -> Result<MsProjectImportReport, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "Project" {
+        return Err("non è un file XML di MS Project".to_string());
+    }
+    let mut report = MsProjectImportReport::default();
+
+    let title = [child_text(root, "Title"), child_text(root, "Name")]
+        .into_iter()
+        .find(|t| !t.is_empty())
+        .unwrap_or("")
+        .trim_end_matches(".xml")
+        .trim_end_matches(".mpp")
+        .to_string();
+
+    // Tasks are listed in outline order: the summary tasks come first
+    let mut tasks: HashMap<&str, Task> = HashMap::new();
+    let mut outline: Vec<String> = vec![];
+    for node in children(root, "Tasks").flat_map(|n| children(n, "Task")) {
+        let level = child_text(node, "OutlineLevel")
+            .parse::<usize>()
+            .unwrap_or(1);
+        let name = child_text(node, "Name").to_string();
+        outline.truncate(level.saturating_sub(1));
+        outline.push(name);
+        tasks.insert(
+            child_text(node, "UID"),
+            Task {
+                names: outline.iter().rev().cloned().collect(),
+            },
+        );
+    }
+
+    let mut resources: HashMap<&str, Resource> = HashMap::new();
+    for node in children(root, "Resources").flat_map(|n| children(n, "Resource")) {
+        let name = child_text(node, "Name");
+        if name.is_empty() {
+            continue;
+        }
+        resources.insert(
+            child_text(node, "UID"),
+            Resource {
+                name: name.to_string(),
+                group: child_text(node, "Group").to_string(),
+                // 0 material, 1 work, 2 cost
+                is_work: matches!(child_text(node, "Type"), "" | "1"),
+            },
+        );
+    }
+
+    let find_team = |task: &Task, resource: &Resource| -> Option<Devs> {
+        task.names
+            .iter()
+            .find_map(|n| mapping.teams.get(n))
+            .or_else(|| mapping.teams.get(&resource.group))
+            .and_then(|t| Devs::from_title(t))
+            .or_else(|| Devs::from_title(&resource.group))
+            .or_else(|| mapping.team.as_deref().and_then(Devs::from_title))
+    };
+    let find_project = |task: &Task| -> Option<String> {
+        task.names
+            .iter()
+            .find_map(|n| mapping.projects.get(n))
+            .or(mapping.project.as_ref())
+            .cloned()
+            .or_else(|| (!title.is_empty()).then(|| title.clone()))
+    };
+
+    // Hours per (project, team, worker, week)
+    let mut hours: BTreeMap<(String, Devs, String, i32), f64> = BTreeMap::new();
+    let mut skipped_resources = vec![];
+    for node in children(root, "Assignments").flat_map(|n| children(n, "Assignment")) {
+        let (task_uid, resource_uid) =
+            (child_text(node, "TaskUID"), child_text(node, "ResourceUID"));
+        let Some(task) = tasks.get(task_uid) else {
+            continue;
+        };
+        let task_name = task.names.first().cloned().unwrap_or_default();
+        let Some(resource) = resources.get(resource_uid) else {
+            // -65535 is the placeholder of tasks without resources
+            if resource_uid != "-65535" {
+                report.unmapped.push(format!(
+                    "attività \"{}\": risorsa {} sconosciuta",
+                    task_name, resource_uid
+                ));
+            }
+            continue;
+        };
+        if !resource.is_work {
+            if !skipped_resources.contains(&resource.name) {
+                skipped_resources.push(resource.name.clone());
+                report.unmapped.push(format!(
+                    "risorsa \"{}\": non è una risorsa di lavoro",
+                    resource.name
+                ));
+            }
+            continue;
+        }
+        let Some(project) = find_project(task) else {
+            report
+                .unmapped
+                .push(format!("attività \"{}\": nessun progetto", task_name));
+            continue;
+        };
+        let Some(dev) = find_team(task, resource) else {
+            report.unmapped.push(format!(
+                "attività \"{}\" / {}: nessun team",
+                task_name, resource.name
+            ));
+            continue;
+        };
+        let worker = mapping
+            .workers
+            .get(&resource.name)
+            .cloned()
+            .unwrap_or(resource.name.clone())
+            .replace('|', " ");
+
+        let mut weeks = BTreeMap::new();
+        let phased: Vec<_> = children(node, "TimephasedData")
+            .filter(|t| matches!(child_text(*t, "Type"), "1" | "2"))
+            .collect();
+        if phased.is_empty() {
+            let (start, finish) = (
+                parse_datetime(child_text(node, "Start")),
+                parse_datetime(child_text(node, "Finish")),
+            );
+            match (start, finish, parse_hours(child_text(node, "Work"))) {
+                (Some(start), Some(finish), Some(work)) => spread(start, finish, work, &mut weeks),
+                _ => {
+                    report.unmapped.push(format!(
+                        "attività \"{}\" / {}: date o lavoro non validi",
+                        task_name, resource.name
+                    ));
+                    continue;
+                }
+            }
+        }
+        for item in phased.into_iter() {
+            let start = parse_datetime(child_text(item, "Start"));
+            let finish = parse_datetime(child_text(item, "Finish"));
+            if let (Some(start), Some(finish), Some(work)) =
+                (start, finish, parse_hours(child_text(item, "Value")))
+            {
+                spread(start, finish, work, &mut weeks);
+            }
+        }
+
+        for (week, h) in weeks.into_iter() {
+            *hours
+                .entry((project.clone(), dev, worker.clone(), week))
+                .or_insert(0.0) += h;
+        }
+    }
+
+    let rows = hours
+        .into_iter()
+        .map(|((project, dev, worker, week), h)| Row {
+            project,
+            dev,
+            worker,
+            week,
+            percent: (h * 100.0 / HOURS_PER_WEEK as f64).round() as i32,
+        })
+        .filter(|row| row.percent > 0)
+        .collect();
+    apply_rows(efforts, rows, mode, &mut report.assignments);
+
+    Ok(report)
+}
+
+/// Imports an MS Project XML file into the plan.
+///
+/// # Returns
+/// * `Ok(MsProjectImportReport)` - The outcome, with what could not be mapped
+/// * `Err(String)` - If the file cannot be read or is not an MS Project XML file
+pub fn import_msproject_file(
+    efforts: &mut EffortsDto,
+    path: &str,
+    mapping: &MsProjectMapping,
+    mode: ImportMode,
+) -> Result<MsProjectImportReport, String> {
+    let xml = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    import_msproject(efforts, &xml, mapping, mode)
+}
+
+/// Reads a mapping file.
+pub fn load_mapping(path: &str) -> Result<MsProjectMapping, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+/// The mapping used when none is given: `<name>.mapping.json` next to the XML
+/// file, or the default mapping if there is none.
+pub fn mapping_for(xml_path: &str) -> Result<MsProjectMapping, String> {
+    let path = std::path::Path::new(xml_path).with_extension("mapping.json");
+    if path.exists() {
+        load_mapping(&path.to_string_lossy())
+    } else {
+        Ok(MsProjectMapping::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Project xmlns="http://schemas.microsoft.com/project">
+  <Title>Scheda</Title>
+  <Tasks>
+    <Task><UID>1</UID><Name>Scheda controllo</Name><OutlineLevel>1</OutlineLevel></Task>
+    <Task><UID>2</UID><Name>Layout PCB</Name><OutlineLevel>2</OutlineLevel></Task>
+    <Task><UID>3</UID><Name>Collaudo</Name><OutlineLevel>2</OutlineLevel></Task>
+    <Task><UID>4</UID><Name>Documentazione</Name><OutlineLevel>1</OutlineLevel></Task>
+  </Tasks>
+  <Resources>
+    <Resource><UID>1</UID><Name>M. Rossi</Name><Type>1</Type><Group>Ele</Group></Resource>
+    <Resource><UID>2</UID><Name>Anna</Name><Type>1</Type></Resource>
+    <Resource><UID>3</UID><Name>Rame</Name><Type>0</Type></Resource>
+  </Resources>
+  <Assignments>
+    <Assignment>
+      <TaskUID>2</TaskUID><ResourceUID>1</ResourceUID>
+      <Start>2026-10-12T08:00:00</Start><Finish>2026-10-23T17:00:00</Finish>
+      <Work>PT40H0M0S</Work>
+    </Assignment>
+    <Assignment>
+      <TaskUID>3</TaskUID><ResourceUID>2</ResourceUID>
+      <Start>2026-10-12T08:00:00</Start><Finish>2026-10-14T08:00:00</Finish>
+      <Work>PT16H0M0S</Work>
+      <TimephasedData><Type>1</Type><Start>2026-10-12T08:00:00</Start><Finish>2026-10-13T08:00:00</Finish><Value>PT8H0M0S</Value></TimephasedData>
+      <TimephasedData><Type>1</Type><Start>2026-10-13T08:00:00</Start><Finish>2026-10-14T08:00:00</Finish><Value>PT12H0M0S</Value></TimephasedData>
+    </Assignment>
+    <Assignment>
+      <TaskUID>4</TaskUID><ResourceUID>2</ResourceUID>
+      <Start>2026-10-12T08:00:00</Start><Finish>2026-10-12T17:00:00</Finish>
+      <Work>PT8H0M0S</Work>
+    </Assignment>
+    <Assignment>
+      <TaskUID>2</TaskUID><ResourceUID>3</ResourceUID>
+      <Start>2026-10-12T08:00:00</Start><Finish>2026-10-12T17:00:00</Finish>
+      <Work>PT0H0M0S</Work>
+    </Assignment>
+  </Assignments>
+</Project>"#;
+
+    fn mapping() -> MsProjectMapping {
+        MsProjectMapping {
+            projects: BTreeMap::from([("Scheda controllo".to_string(), "Alpha".to_string())]),
+            teams: BTreeMap::from([("Collaudo".to_string(), "Hw Test".to_string())]),
+            workers: BTreeMap::from([("M. Rossi".to_string(), "Mario Rossi".to_string())]),
+            project: None,
+            team: None,
+        }
+    }
+
+    fn cell(efforts: &EffortsDto, project: &str, dev: Devs, day: &str) -> Vec<String> {
+        let week = week_of(NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap());
+        let prj = efforts.projects.iter().find(|p| p.text == project).unwrap();
+        let team = prj.efforts.iter().find(|d| d.dev == dev).unwrap();
+        let data = team.datas.iter().find(|d| d.week == week).unwrap();
+        data.persons
+            .iter()
+            .filter(|p| !p.is_empty())
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_hours() {
+        assert_eq!(parse_hours("PT7H30M0S"), Some(7.5));
+        assert_eq!(parse_hours("P1DT2H0M0S"), Some(26.0));
+        assert_eq!(parse_hours("40h"), None);
+    }
+
+    #[test]
+    fn test_import() {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        let report = import_msproject(&mut efforts, XML, &mapping(), ImportMode::Merge).unwrap();
+
+        // 40h over two weeks, team from the resource group
+        assert_eq!(
+            cell(&efforts, "Alpha", Devs::Ele, "2026-10-12"),
+            vec!["Mario Rossi|50"]
+        );
+        assert_eq!(
+            cell(&efforts, "Alpha", Devs::Ele, "2026-10-19"),
+            vec!["Mario Rossi|50"]
+        );
+        // Time-phased work, team from the task
+        assert_eq!(
+            cell(&efforts, "Alpha", Devs::TestHw, "2026-10-12"),
+            vec!["Anna|50"]
+        );
+
+        // No team for Anna on "Documentazione", material resource skipped
+        assert_eq!(report.unmapped.len(), 2);
+        assert!(report.unmapped[0].contains("Documentazione"));
+        assert!(report.unmapped[1].contains("Rame"));
+        assert_eq!(report.assignments.imported, 3);
+        assert!(efforts.worker_names.contains(&"Mario Rossi".to_string()));
+    }
+
+    #[test]
+    fn test_default_project_and_team() {
+        let mut efforts = EffortsDto::default();
+        let mapping = MsProjectMapping {
+            team: Some("Pjm".to_string()),
+            ..MsProjectMapping::default()
+        };
+        let report = import_msproject(&mut efforts, XML, &mapping, ImportMode::Merge).unwrap();
+
+        // Every task goes to the project named after the file
+        assert_eq!(report.assignments.created_projects, vec!["Scheda"]);
+        assert_eq!(
+            cell(&efforts, "Scheda", Devs::Pjm, "2026-10-12"),
+            vec!["Anna|70"]
+        );
+        assert_eq!(report.unmapped.len(), 1);
+    }
+
+    #[test]
+    fn test_not_msproject() {
+        let mut efforts = EffortsDto::default();
+        let result = import_msproject(&mut efforts, "<Other/>", &mapping(), ImportMode::Merge);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Import of projects, or of some of their teams, from another effort file.
+//!
+//! The imported projects are appended with new ids, their workers are added to
+//! `worker_names` and the week ranges of all projects are aligned before the
+//! totals and the over-allocation are recomputed.
+
+use std::fmt;
+
+use crate::models::{Devs, EffortByPrjDto, EffortsDto};
+
+/// A project to import from the other plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectSelection {
+    /// Id of the project in the other plan.
+    pub project: i32,
+    /// Teams to copy; empty means all of them. The other teams are left empty.
+    pub teams: Vec<Devs>,
+}
+
+/// Outcome of [`import_projects`].
+#[derive(Debug, Clone, Default)]
+pub struct ProjectImportReport {
+    /// Names of the imported projects.
+    pub projects: Vec<String>,
+    /// Workers that were not in the plan yet.
+    pub new_workers: Vec<String>,
+    /// Selected ids not found in the other plan.
+    pub missing: Vec<i32>,
+}
+
+impl fmt::Display for ProjectImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Progetti importati: {}", self.projects.len())?;
+        for name in self.projects.iter() {
+            writeln!(f, "  {}", name)?;
+        }
+        if !self.new_workers.is_empty() {
+            writeln!(f, "Nuovi worker: {}", self.new_workers.join(", "))?;
+        }
+        for id in self.missing.iter() {
+            writeln!(f, "Progetto {} non trovato", id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Empties a team: no budget, no rows, no assignments.
+fn clear_team(prj: &mut EffortByPrjDto, keep: &[Devs]) {
+    for team in prj.efforts.iter_mut().filter(|d| !keep.contains(&d.dev)) {
+        team.effort = 0;
+        team.max = 1;
+        for data in team.datas.iter_mut() {
+            data.effort = 0;
+            data.persons = vec![String::new()];
+        }
+    }
+}
+
+/// Copies the selected projects of `source` into `efforts`.
+///
+/// # Arguments
+/// * `efforts` - The plan receiving the projects
+/// * `source` - The plan to copy from
+/// * `selection` - The projects, and optionally teams, to copy
+pub fn import_projects(
+    efforts: &mut EffortsDto,
+    source: &EffortsDto,
+    selection: &[ProjectSelection],
+) -> ProjectImportReport {
+    let mut report = ProjectImportReport::default();
+    let first_new = efforts.projects.len() as i32;
+
+    for choice in selection.iter() {
+        let Some(prj) = source.projects.iter().find(|p| p.project == choice.project) else {
+            report.missing.push(choice.project);
+            continue;
+        };
+
+        let mut prj = prj.clone();
+        prj.set_project_id(efforts.projects.len() as i32);
+        prj.source.clear();
+        if !choice.teams.is_empty() {
+            clear_team(&mut prj, &choice.teams);
+        }
+
+        report.projects.push(prj.text.clone());
+        efforts.projects.push(prj);
+    }
+
+    for a in efforts.assignments() {
+        if a.project >= first_new && !efforts.worker_names.contains(&a.worker) {
+            efforts.worker_names.push(a.worker.clone());
+            report.new_workers.push(a.worker);
+        }
+    }
+
+    if !report.projects.is_empty() {
+        let (start_week, end_week) = efforts.start_end_weeks();
+        for prj in efforts.projects.iter_mut() {
+            prj.set_date(start_week, end_week);
+        }
+        efforts.rebuild();
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn other_plan() -> EffortsDto {
+        let mut other = EffortsDto::default();
+        let prj = &mut other.projects[0];
+        prj.text = "Gamma".to_string();
+        prj.efforts[0].effort = 100;
+        prj.efforts[0].datas[0].persons = vec!["Anna|50".to_string()];
+        prj.efforts[3].effort = 60;
+        prj.efforts[3].datas[1].persons = vec!["Mario|20".to_string()];
+
+        // Starts a month before the plan receiving it
+        let start = prj.start_week - 28;
+        prj.set_date(start, prj.end_week);
+        other.rebuild();
+        other
+    }
+
+    #[test]
+    fn test_import_whole_project() {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|50".to_string()];
+        efforts.rebuild();
+        let other = other_plan();
+
+        let report = import_projects(
+            &mut efforts,
+            &other,
+            &[ProjectSelection {
+                project: 0,
+                teams: vec![],
+            }],
+        );
+
+        assert_eq!(report.projects, vec!["Gamma"]);
+        assert_eq!(report.new_workers, vec!["Anna"]);
+        assert_eq!(efforts.projects.len(), 2);
+
+        let imported = &efforts.projects[1];
+        assert_eq!(imported.project, 1);
+        assert!(imported.efforts[0].datas.iter().all(|d| d.project == 1));
+        assert_eq!(imported.efforts[3].effort, 60);
+
+        // Both projects cover the same weeks
+        assert_eq!(efforts.projects[0].start_week, imported.start_week);
+        assert_eq!(efforts.projects[0].end_week, imported.end_week);
+        assert_eq!(efforts.worker_names, vec!["Mario", "Anna"]);
+    }
+
+    #[test]
+    fn test_import_some_teams() {
+        let mut efforts = EffortsDto::default();
+        let report = import_projects(
+            &mut efforts,
+            &other_plan(),
+            &[
+                ProjectSelection {
+                    project: 0,
+                    teams: vec![Devs::Hw],
+                },
+                ProjectSelection {
+                    project: 5,
+                    teams: vec![],
+                },
+            ],
+        );
+
+        assert_eq!(report.missing, vec![5]);
+        let imported = &efforts.projects[1];
+        assert_eq!(imported.efforts[0].effort, 0);
+        assert_eq!(imported.efforts[0].total, 0);
+        assert_eq!(imported.efforts[3].effort, 60);
+        assert_eq!(report.new_workers, vec!["Mario"]);
+    }
+}
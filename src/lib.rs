@@ -16,7 +16,17 @@
 //! - [`utils`] - Utility functions for calculations and parsing
 //! - [`date_utils`] - Date and week manipulation functions
 //! - [`file_io`] - File saving and loading operations
+//! - `sqlite` - SQLite storage backend (feature `sqlite`)
+//! - [`workspace`] - Several effort files shown and saved as one plan
+//! - [`schema`] - JSON Schema of the effort file format
 //! - [`callbacks`] - UI callback handlers
+//! - [`exporters`] - CSV and other exports of the plan
+//! - [`importers`] - CSV and other imports into the plan
+//! - [`merge`] - Three-way merge of two copies of a plan
+//! - [`diff`] - Comparison of two plans in planning terms
+//! - [`validate`] - Checks of a loaded plan, with safe automatic fixes
+//! - [`workers`] - Worker names: suggestions, look-alikes and renaming
+//! - [`cli`] - Command line subcommands
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
@@ -26,4 +36,15 @@ pub mod models;
 pub mod utils;
 pub mod date_utils;
 pub mod file_io;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod workspace;
+pub mod schema;
 pub mod callbacks;
+pub mod exporters;
+pub mod importers;
+pub mod merge;
+pub mod diff;
+pub mod validate;
+pub mod workers;
+pub mod cli;
@@ -4,7 +4,7 @@
 use project_app::*;
 
 use chrono::{NaiveDate, Utc};
-use slint::{Model, ModelRc, SharedString, VecModel};
+use slint::{SharedString, VecModel};
 use std::{cell::RefCell, error::Error, rc::Rc};
 
 use project_app::{callbacks::*, date_utils::*, file_io::*};
@@ -12,12 +12,14 @@ use project_app::{callbacks::*, date_utils::*, file_io::*};
 struct AppArgs {
     file: String,
     start_date: Option<NaiveDate>,
+    readonly: bool,
 }
 
 fn parse_args() -> AppArgs {
     let args: Vec<String> = std::env::args().collect();
     let mut file = "efforts.json".to_string();
     let mut start_date = None;
+    let mut readonly = false;
 
     let mut i = 1;
     while i < args.len() {
@@ -37,6 +39,9 @@ fn parse_args() -> AppArgs {
                     }
                 }
             }
+            "--readonly" | "-r" => {
+                readonly = true;
+            }
             arg if !arg.starts_with('-') => {
                 file = arg.to_string();
             }
@@ -47,80 +52,58 @@ fn parse_args() -> AppArgs {
         i += 1;
     }
 
-    AppArgs { file, start_date }
-}
-
-fn populate_models(
-    mut app_info: project_app::models::EffortsDto,
-    start_date_override: Option<NaiveDate>,
-    vec_model_projects: &Rc<VecModel<EffortByPrjData>>,
-    vec_model_week_off: &Rc<VecModel<i32>>,
-    vec_model_worker_names: &Rc<VecModel<SharedString>>,
-    vec_model_sovra: &Rc<VecModel<SovraData>>,
-    ui: &AppWindow,
-) {
-    // Clear existing data
-    for i in (0..vec_model_projects.row_count()).rev() {
-        vec_model_projects.remove(i);
-    }
-    for i in (0..vec_model_week_off.row_count()).rev() {
-        vec_model_week_off.remove(i);
-    }
-    for i in (0..vec_model_worker_names.row_count()).rev() {
-        vec_model_worker_names.remove(i);
-    }
-    for i in (0..vec_model_sovra.row_count()).rev() {
-        vec_model_sovra.remove(i);
-    }
-
-    let (mut start_week, end_week) = app_info.start_end_weeks();
-
-    if let Some(date) = start_date_override {
-        start_week = local_to_days(&primo_giorno_settimana_corrente(&date));
-    }
-
-    println!("start_week: {} - end_week: {}", start_week, end_week);
-
-    app_info.sync_sovra(start_week, end_week);
-
-    let weeks_day_dto = weeks_list(&days_to_local(start_week), &days_to_local(end_week));
-    let weeks_day_data = ModelRc::new(VecModel::from(
-        weeks_day_dto
-            .into_iter()
-            .map(DayData::from)
-            .collect::<Vec<_>>(),
-    ));
-    ui.set_weeks(weeks_day_data);
-
-    for dto in app_info.projects.iter_mut() {
-        dto.set_date(start_week, end_week);
-    }
-
-    for dto in app_info.projects.into_iter() {
-        vec_model_projects.push(dto.into());
-    }
-
-    for person in app_info.worker_names {
-        vec_model_worker_names.push(person.into());
-    }
-
-    for sovra in app_info.sovra {
-        vec_model_sovra.push(sovra.into());
+    AppArgs {
+        file,
+        start_date,
+        readonly,
     }
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // Subcommands (export, ...) run without opening the GUI
+    let argv: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&argv) {
+        std::process::exit(code);
+    }
+
     let args = parse_args();
     let ui = AppWindow::new()?;
 
     let vec_model_projects = Rc::new(VecModel::<EffortByPrjData>::default());
     let vec_model_week_off = Rc::new(VecModel::<i32>::default());
+    let vec_model_holidays = Rc::new(VecModel::<HolidayData>::default());
     let vec_model_worker_names = Rc::new(VecModel::<SharedString>::default());
     let vec_model_sovra = Rc::new(VecModel::<SovraData>::default());
 
+    ui.set_efforts(EffortsData {
+        sovra: vec_model_sovra.clone().into(),
+        week_off: vec_model_week_off.clone().into(),
+        holidays: vec_model_holidays.clone().into(),
+        projects: vec_model_projects.clone().into(),
+        worker_names: vec_model_worker_names.clone().into(),
+        workers: Default::default(),
+        calendar: CalendarData::default(),
+        load: models::LoadTargetDto::default().into(),
+    });
+
     let current_file = Rc::new(RefCell::new(args.file.clone()));
 
-    let app_info = load_efforts_from_file(&args.file);
+    // A default plan in place of an unreadable file would overwrite it on save
+    let app_info = if std::path::Path::new(&args.file).exists() {
+        match read_efforts_file(&args.file) {
+            Ok(efforts) => efforts,
+            Err(e) => {
+                rfd::MessageDialog::new()
+                    .set_title("Apertura del piano")
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_description(e)
+                    .show();
+                return Ok(());
+            }
+        }
+    } else {
+        load_efforts_from_file(&args.file)
+    };
 
     // Show filename (without full path) in title bar
     let display_name = std::path::Path::new(&args.file)
@@ -128,26 +111,29 @@ fn main() -> Result<(), Box<dyn Error>> {
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or(args.file.clone());
     PjmCallback::get(&ui).set_current_file(display_name.into());
+    PjmCallback::get(&ui).set_readonly(args.readonly || !is_file_writable(&args.file));
+    PjmCallback::get(&ui).set_autosave(is_sqlite_path(&args.file));
 
-    populate_models(
-        app_info,
-        args.start_date,
-        &vec_model_projects,
-        &vec_model_week_off,
-        &vec_model_worker_names,
-        &vec_model_sovra,
-        &ui,
-    );
+    let plan_models = PlanModels {
+        projects: vec_model_projects.clone(),
+        week_off: vec_model_week_off.clone(),
+        holidays: vec_model_holidays.clone(),
+        worker_names: vec_model_worker_names.clone(),
+        sovra: vec_model_sovra.clone(),
+    };
 
     let this_week = local_to_days(&primo_giorno_settimana_corrente(&Utc::now().date_naive()));
     PjmCallback::get(&ui).set_this_week(this_week);
     println!("this_week: {}", this_week);
 
+    populate_models(app_info, args.start_date, &plan_models, &ui);
+
     // Register all callbacks
     register_on_save_file(
         &ui,
         vec_model_projects.clone(),
         vec_model_week_off.clone(),
+        vec_model_holidays.clone(),
         vec_model_worker_names.clone(),
         vec_model_sovra.clone(),
         current_file.clone(),
@@ -162,7 +148,10 @@ fn main() -> Result<(), Box<dyn Error>> {
         vec_model_projects.clone(),
         vec_model_worker_names.clone(),
         vec_model_sovra.clone(),
+        vec_model_week_off.clone(),
+        vec_model_holidays.clone(),
     );
+    register_on_check_cell(&ui, vec_model_worker_names.clone());
 
     register_on_search(&ui, vec_model_projects.clone());
 
@@ -170,31 +159,58 @@ fn main() -> Result<(), Box<dyn Error>> {
     register_on_add_row(&ui, vec_model_projects.clone());
     register_on_hide_dev(&ui, vec_model_projects.clone());
 
+    register_on_export_csv(&ui);
+    register_on_export_xlsx(&ui);
+    register_on_export_html(&ui);
+    register_on_export_ics(&ui);
+    register_on_export_anonymized(&ui);
+    register_on_import_csv(&ui, plan_models.clone());
+    register_on_import_ics(&ui, plan_models.clone());
+    register_on_import_msproject(&ui, plan_models.clone());
+    register_on_import_projects(&ui, plan_models.clone());
+    register_on_validate(&ui, plan_models.clone());
+    register_on_rename_worker(&ui, plan_models.clone());
+    register_on_roster(&ui, plan_models.clone());
+    register_on_set_italian_holidays(&ui, plan_models.clone());
+    register_on_merge(&ui, plan_models.clone());
+
     register_on_move_effort(
         &ui,
         vec_model_projects.clone(),
         vec_model_sovra.clone(),
         vec_model_worker_names.clone(),
+        vec_model_week_off.clone(),
+        vec_model_holidays.clone(),
     );
 
     // Register open file callback (Ctrl+O)
     {
         let ui_weak = ui.as_weak();
-        let vm_projects = vec_model_projects.clone();
-        let vm_week_off = vec_model_week_off.clone();
-        let vm_worker_names = vec_model_worker_names.clone();
-        let vm_sovra = vec_model_sovra.clone();
+        let models = plan_models.clone();
         let cf = current_file.clone();
+        let force_readonly = args.readonly;
 
         PjmCallback::get(&ui).on_open_file(move || {
-            let path = rfd::FileDialog::new()
-                .add_filter("JSON files", &["json"])
-                .set_title("Apri file effort")
-                .pick_file();
+            let dialog = rfd::FileDialog::new().add_filter("JSON files", &["json"]);
+            #[cfg(feature = "sqlite")]
+            let dialog = dialog.add_filter("SQLite databases", &["db", "sqlite", "sqlite3"]);
+            let path = dialog.set_title("Apri file effort").pick_file();
 
             if let Some(path) = path {
                 let path_str = path.to_string_lossy().to_string();
-                let app_info = load_efforts_from_file(&path_str);
+                let app_info = match read_efforts_file(&path_str) {
+                    Ok(app_info) => app_info,
+                    Err(e) => {
+                        // Keep the current plan rather than showing an empty one
+                        println!("Error during load the file \"{}\": {}", path_str, e);
+                        rfd::MessageDialog::new()
+                            .set_title("Open file")
+                            .set_level(rfd::MessageLevel::Error)
+                            .set_description(format!("{}\n\n{}", path_str, e))
+                            .show();
+                        return;
+                    }
+                };
                 *cf.borrow_mut() = path_str.clone();
 
                 if let Some(ui) = ui_weak.upgrade() {
@@ -203,28 +219,17 @@ fn main() -> Result<(), Box<dyn Error>> {
                         .map(|n| n.to_string_lossy().to_string())
                         .unwrap_or(path_str.clone());
                     PjmCallback::get(&ui).set_current_file(display_name.into());
+                    PjmCallback::get(&ui)
+                        .set_readonly(force_readonly || !is_file_writable(&path_str));
+                    PjmCallback::get(&ui).set_autosave(is_sqlite_path(&path_str));
 
-                    populate_models(
-                        app_info,
-                        None,
-                        &vm_projects,
-                        &vm_week_off,
-                        &vm_worker_names,
-                        &vm_sovra,
-                        &ui,
-                    );
+                    populate_models(app_info, None, &models, &ui);
                     PjmCallback::get(&ui).set_changed(false);
                 }
             }
         });
     }
 
-    ui.set_efforts(EffortsData {
-        sovra: vec_model_sovra.into(),
-        week_off: vec_model_week_off.into(),
-        projects: vec_model_projects.into(),
-        worker_names: vec_model_worker_names.into(),
-    });
     ui.run()?;
 
     Ok(())
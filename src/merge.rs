@@ -0,0 +1,518 @@
+//! Three-way merge of effort plans.
+//!
+//! Two copies of the same plan (`ours` and `theirs`) are merged against the
+//! common ancestor (`base`) cell by cell, where a cell is identified by
+//! (project, team, week, row). A change made on one side only is taken
+//! automatically; when both sides changed the same cell differently the cell
+//! keeps our value and is reported as a [`Conflict`] to be resolved.
+//!
+//! Projects are matched by id. Projects added by both sides keep our ids, and
+//! the ones added by theirs are appended with new ids. A project removed by one
+//! side is removed if the other side left it as it was; if the other side
+//! changed it the plans cannot be merged.
+
+use std::fmt;
+
+use crate::date_utils::date_text;
+use crate::models::effort_by_dev::EffortByDevDto;
+use crate::models::{Devs, EffortByPrjDto, EffortsDto};
+
+/// The element of a project that both sides changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictAt {
+    /// Project name.
+    Name,
+    /// Team budget, in hours.
+    Budget(Devs),
+    /// Content of a cell.
+    Cell { dev: Devs, week: i32, row: usize },
+}
+
+/// A value changed differently by both sides.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    /// Index of the project in the merged plan.
+    pub project: usize,
+    pub project_name: String,
+    pub at: ConflictAt,
+    pub base: String,
+    pub ours: String,
+    pub theirs: String,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.at {
+            ConflictAt::Name => write!(f, "{} / nome", self.project_name)?,
+            ConflictAt::Budget(dev) => {
+                write!(f, "{} / {} / budget", self.project_name, dev.title())?
+            }
+            ConflictAt::Cell { dev, week, row } => write!(
+                f,
+                "{} / {} / {} / riga {}",
+                self.project_name,
+                dev.title(),
+                date_text(week),
+                row + 1
+            )?,
+        }
+        write!(
+            f,
+            ": base \"{}\", nostra \"{}\", loro \"{}\"",
+            self.base, self.ours, self.theirs
+        )
+    }
+}
+
+/// Which value wins a conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Base,
+}
+
+/// Outcome of [`merge`].
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// The merged plan. Conflicting values hold our side.
+    pub merged: EffortsDto,
+    /// Number of values taken from theirs without conflict.
+    pub from_theirs: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Returns the merged value, or `None` when both sides changed it differently.
+fn pick<'a, T: PartialEq + ?Sized>(base: &'a T, ours: &'a T, theirs: &'a T) -> Option<&'a T> {
+    if ours == theirs || theirs == base {
+        Some(ours)
+    } else if ours == base {
+        Some(theirs)
+    } else {
+        None
+    }
+}
+
+/// Three-way merge of a set: an item is kept if both sides have it, or if the side
+/// that has it added it.
+fn merge_set<T: PartialEq + Clone>(base: &[T], ours: &[T], theirs: &[T]) -> Vec<T> {
+    let mut merged: Vec<T> = ours
+        .iter()
+        .filter(|item| theirs.contains(item) || !base.contains(item))
+        .cloned()
+        .collect();
+    for item in theirs.iter() {
+        if !ours.contains(item) && !base.contains(item) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+fn persons(dev: Option<&EffortByDevDto>, week: i32) -> &[String] {
+    dev.and_then(|d| d.datas.iter().find(|data| data.week == week))
+        .map_or(&[], |data| data.persons.as_slice())
+}
+
+/// Whether a project is the same in both plans.
+fn same_project(a: &EffortByPrjDto, b: &EffortByPrjDto) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+struct ProjectMerge<'a> {
+    /// Index in the merged plan.
+    project: usize,
+    id: i32,
+    base: Option<&'a EffortByPrjDto>,
+    ours: Option<&'a EffortByPrjDto>,
+    theirs: Option<&'a EffortByPrjDto>,
+}
+
+impl<'a> ProjectMerge<'a> {
+    fn run(&self, result: &mut MergeResult) -> EffortByPrjDto {
+        let mut merged = self.ours.or(self.theirs).unwrap().clone();
+        merged.set_project_id(self.id);
+
+        let text = |p: Option<&EffortByPrjDto>| p.map(|p| p.text.clone()).unwrap_or_default();
+        let (base, ours, theirs) = (text(self.base), text(self.ours), text(self.theirs));
+        match pick(&base, &ours, &theirs) {
+            Some(value) => {
+                if *value != ours {
+                    result.from_theirs += 1;
+                }
+                merged.text = value.clone();
+            }
+            None => result.conflicts.push(Conflict {
+                project: self.project,
+                project_name: ours.clone(),
+                at: ConflictAt::Name,
+                base,
+                ours,
+                theirs,
+            }),
+        }
+
+        let sides = [self.ours, self.theirs];
+        let start_week = sides.iter().flatten().map(|p| p.start_week).min().unwrap();
+        let end_week = sides.iter().flatten().map(|p| p.end_week).max().unwrap();
+        merged.set_date(start_week, end_week);
+
+        let project_name = merged.text.clone();
+        for dev in merged.efforts.iter_mut() {
+            self.merge_dev(dev, &project_name, result);
+        }
+        merged
+    }
+
+    fn merge_dev(&self, dev: &mut EffortByDevDto, project_name: &str, result: &mut MergeResult) {
+        let team = dev.dev;
+        let find = |p: Option<&'a EffortByPrjDto>| {
+            p.and_then(|p| p.efforts.iter().find(|d| d.dev == team))
+        };
+        let (base, ours, theirs) = (find(self.base), find(self.ours), find(self.theirs));
+
+        let budget = |d: Option<&EffortByDevDto>| d.map_or(0, |d| d.effort);
+        let (b, o, t) = (budget(base), budget(ours), budget(theirs));
+        match pick(&b, &o, &t) {
+            Some(value) => {
+                if *value != o {
+                    result.from_theirs += 1;
+                }
+                dev.effort = *value;
+            }
+            None => result.conflicts.push(Conflict {
+                project: self.project,
+                project_name: project_name.to_string(),
+                at: ConflictAt::Budget(dev.dev),
+                base: b.to_string(),
+                ours: o.to_string(),
+                theirs: t.to_string(),
+            }),
+        }
+
+        let enable = |d: Option<&EffortByDevDto>| d.is_some_and(|d| d.enable);
+        dev.enable = *pick(&enable(base), &enable(ours), &enable(theirs)).unwrap_or(&true);
+
+        let rows = [base, ours, theirs]
+            .iter()
+            .flatten()
+            .flat_map(|d| d.datas.iter().map(|data| data.persons.len()))
+            .max()
+            .unwrap_or(1)
+            .max(1);
+
+        for data in dev.datas.iter_mut() {
+            let (b, o, t) = (
+                persons(base, data.week),
+                persons(ours, data.week),
+                persons(theirs, data.week),
+            );
+            let cell = |side: &[String], row: usize| side.get(row).cloned().unwrap_or_default();
+            data.persons = (0..rows)
+                .map(|row| {
+                    let (b, o, t) = (cell(b, row), cell(o, row), cell(t, row));
+                    match pick(b.trim(), o.trim(), t.trim()) {
+                        Some(_) if o.trim() == t.trim() || t.trim() == b.trim() => o,
+                        Some(_) => {
+                            result.from_theirs += 1;
+                            t
+                        }
+                        None => {
+                            result.conflicts.push(Conflict {
+                                project: self.project,
+                                project_name: project_name.to_string(),
+                                at: ConflictAt::Cell {
+                                    dev: dev.dev,
+                                    week: data.week,
+                                    row,
+                                },
+                                base: b,
+                                ours: o.clone(),
+                                theirs: t,
+                            });
+                            o
+                        }
+                    }
+                })
+                .collect();
+        }
+        dev.max = rows as i32;
+    }
+}
+
+/// Merges two copies of a plan against their common ancestor.
+///
+/// Totals, worker list and over-allocation of the result are recomputed with
+/// [`EffortsDto::rebuild`]. Conflicts keep our value until [`resolve_conflicts`].
+///
+/// # Arguments
+/// * `base` - The common ancestor
+/// * `ours` - Our copy
+/// * `theirs` - Their copy
+///
+/// # Returns
+/// * `Err(String)` - If a project was removed by one side and changed by the other
+pub fn merge(
+    base: &EffortsDto,
+    ours: &EffortsDto,
+    theirs: &EffortsDto,
+) -> Result<MergeResult, String> {
+    let mut result = MergeResult {
+        merged: ours.clone(),
+        from_theirs: 0,
+        conflicts: vec![],
+    };
+    let find = |plan: &'_ EffortsDto, id: i32| -> Option<usize> {
+        plan.projects.iter().position(|p| p.project == id)
+    };
+    let removed = |prj: &EffortByPrjDto| {
+        format!(
+            "Progetto \"{}\" rimosso da una parte e modificato dall'altra",
+            prj.text
+        )
+    };
+    let mut next_id = ours
+        .projects
+        .iter()
+        .chain(theirs.projects.iter())
+        .map(|p| p.project + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut projects = vec![];
+    for prj in ours.projects.iter() {
+        let base_prj = find(base, prj.project).map(|i| &base.projects[i]);
+        // A project added by both sides is the same only if it has the same name
+        let theirs_prj = find(theirs, prj.project)
+            .map(|i| &theirs.projects[i])
+            .filter(|t| base_prj.is_some() || t.text.trim() == prj.text.trim());
+        if let Some(base_prj) = base_prj
+            && theirs_prj.is_none()
+        {
+            // Removed by theirs
+            if same_project(base_prj, prj) {
+                continue;
+            }
+            return Err(removed(prj));
+        }
+        let step = ProjectMerge {
+            project: projects.len(),
+            id: prj.project,
+            base: base_prj,
+            ours: Some(prj),
+            theirs: theirs_prj,
+        };
+        projects.push(step.run(&mut result));
+    }
+    for prj in theirs.projects.iter() {
+        let base_prj = find(base, prj.project).map(|i| &base.projects[i]);
+        let merged_already = find(ours, prj.project)
+            .is_some_and(|i| base_prj.is_some() || prj.text.trim() == ours.projects[i].text.trim());
+        if merged_already {
+            continue;
+        }
+        if let Some(base_prj) = base_prj {
+            // Removed by ours
+            if same_project(base_prj, prj) {
+                continue;
+            }
+            return Err(removed(prj));
+        }
+        let step = ProjectMerge {
+            project: projects.len(),
+            id: next_id,
+            base: None,
+            ours: None,
+            theirs: Some(prj),
+        };
+        next_id += 1;
+        projects.push(step.run(&mut result));
+    }
+
+    let merged = &mut result.merged;
+    merged.projects = projects;
+    merged.worker_names = merge_set(&base.worker_names, &ours.worker_names, &theirs.worker_names);
+    merged.week_off = merge_set(&base.week_off, &ours.week_off, &theirs.week_off);
+    merged.holidays = merge_set(&base.holidays, &ours.holidays, &theirs.holidays);
+    merged.calendar = pick(&base.calendar, &ours.calendar, &theirs.calendar)
+        .unwrap_or(&ours.calendar)
+        .clone();
+    merged.load = *pick(&base.load, &ours.load, &theirs.load).unwrap_or(&ours.load);
+    merged.workers = pick(&base.workers, &ours.workers, &theirs.workers)
+        .unwrap_or(&ours.workers)
+        .clone();
+    merged.rebuild();
+
+    Ok(result)
+}
+
+/// Applies the chosen value to each conflict, then recomputes the plan.
+///
+/// # Arguments
+/// * `merged` - The merged plan returned by [`merge`]
+/// * `conflicts` - The conflicts returned by [`merge`]
+/// * `choices` - One resolution per conflict
+pub fn resolve_conflicts(merged: &mut EffortsDto, conflicts: &[Conflict], choices: &[Resolution]) {
+    for (conflict, choice) in conflicts.iter().zip(choices.iter()) {
+        let value = match choice {
+            Resolution::Ours => &conflict.ours,
+            Resolution::Theirs => &conflict.theirs,
+            Resolution::Base => &conflict.base,
+        };
+        let Some(prj) = merged.projects.get_mut(conflict.project) else {
+            continue;
+        };
+        match conflict.at {
+            ConflictAt::Name => prj.text = value.clone(),
+            ConflictAt::Budget(dev) => {
+                if let Some(d) = prj.efforts.iter_mut().find(|d| d.dev == dev) {
+                    d.effort = value.parse().unwrap_or(d.effort);
+                }
+            }
+            ConflictAt::Cell { dev, week, row } => {
+                let cell = prj
+                    .efforts
+                    .iter_mut()
+                    .find(|d| d.dev == dev)
+                    .and_then(|d| d.datas.iter_mut().find(|data| data.week == week))
+                    .and_then(|data| data.persons.get_mut(row));
+                if let Some(cell) = cell {
+                    *cell = value.clone();
+                }
+            }
+        }
+    }
+    merged.rebuild();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|50".to_string()];
+        efforts.rebuild();
+        efforts
+    }
+
+    fn cell(efforts: &EffortsDto, week: usize) -> &Vec<String> {
+        &efforts.projects[0].efforts[0].datas[week].persons
+    }
+
+    #[test]
+    fn test_non_overlapping_edits() {
+        let base = base();
+        let mut ours = base.clone();
+        ours.projects[0].efforts[0].datas[1].persons = vec!["Lucia|20".to_string()];
+        let mut theirs = base.clone();
+        theirs.projects[0].efforts[0].datas[2].persons = vec!["Anna|40".to_string()];
+        theirs.projects[0].efforts[0].effort = 200;
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+        assert!(result.conflicts.is_empty());
+        assert_eq!(result.from_theirs, 2);
+        let merged = &result.merged;
+        assert_eq!(cell(merged, 0), &vec!["Mario|50"]);
+        assert_eq!(cell(merged, 1), &vec!["Lucia|20"]);
+        assert_eq!(cell(merged, 2), &vec!["Anna|40"]);
+        assert_eq!(merged.projects[0].efforts[0].effort, 200);
+        // Recomputed totals: 50% + 20% + 40% of 40 hours
+        assert_eq!(merged.projects[0].efforts[0].total, 44);
+        assert!(merged.worker_names.contains(&"Anna".to_string()));
+    }
+
+    #[test]
+    fn test_conflicting_cell() {
+        let base = base();
+        let mut ours = base.clone();
+        ours.projects[0].efforts[0].datas[0].persons = vec!["Mario|80".to_string()];
+        let mut theirs = base.clone();
+        theirs.projects[0].efforts[0].datas[0].persons = vec!["Mario|30".to_string()];
+
+        let mut result = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(result.conflicts.len(), 1);
+        let conflict = &result.conflicts[0];
+        assert_eq!(
+            (conflict.ours.as_str(), conflict.theirs.as_str()),
+            ("Mario|80", "Mario|30")
+        );
+        assert_eq!(cell(&result.merged, 0), &vec!["Mario|80"]);
+
+        resolve_conflicts(&mut result.merged, &result.conflicts, &[Resolution::Theirs]);
+        assert_eq!(cell(&result.merged, 0), &vec!["Mario|30"]);
+        assert_eq!(result.merged.projects[0].efforts[0].total, 12);
+    }
+
+    #[test]
+    fn test_projects_added_on_both_sides() {
+        let base = base();
+        let mut ours = base.clone();
+        let mut prj = EffortByPrjDto::new(1);
+        prj.text = "Beta".to_string();
+        ours.projects.push(prj);
+        let mut theirs = base.clone();
+        let mut prj = EffortByPrjDto::new(1);
+        prj.text = "Gamma".to_string();
+        theirs.projects.push(prj);
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+        let names: Vec<&str> = result
+            .merged
+            .projects
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect();
+        assert_eq!(names, vec!["Alpha", "Beta", "Gamma"]);
+        assert_eq!(result.merged.projects[2].project, 2);
+        assert_eq!(result.merged.projects[2].efforts[0].datas[0].project, 2);
+    }
+
+    fn three_projects() -> EffortsDto {
+        let mut efforts = base();
+        for (id, name) in [(1, "Beta"), (2, "Gamma")] {
+            let mut prj = EffortByPrjDto::new(id);
+            prj.text = name.to_string();
+            efforts.projects.push(prj);
+        }
+        efforts.rebuild();
+        efforts
+    }
+
+    #[test]
+    fn test_project_removed_in_the_middle() {
+        let base = three_projects();
+        // Removed as the program does, the next projects take the free ids
+        let mut ours = base.clone();
+        ours.projects.remove(1);
+        for (index, prj) in ours.projects.iter_mut().enumerate() {
+            prj.set_project_id(index as i32);
+        }
+        let mut theirs = base.clone();
+        theirs.projects[0].efforts[0].datas[1].persons = vec!["Anna|40".to_string()];
+
+        let result = merge(&base, &ours, &theirs).unwrap();
+        let names: Vec<&str> = result
+            .merged
+            .projects
+            .iter()
+            .map(|p| p.text.as_str())
+            .collect();
+        assert_eq!(names, vec!["Alpha", "Gamma"]);
+        assert_eq!(cell(&result.merged, 1), &vec!["Anna|40"]);
+
+        // Removed with the ids kept, while theirs changed it
+        let mut ours = base.clone();
+        ours.projects.remove(1);
+        let mut theirs = base.clone();
+        theirs.projects[1].efforts[0].effort = 100;
+        assert!(merge(&base, &ours, &theirs).is_err());
+        theirs.projects[1].efforts[0].effort = 0;
+        theirs.projects[2].efforts[0].effort = 100;
+        let result = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(result.merged.projects.len(), 2);
+        assert_eq!(result.merged.projects[1].project, 2);
+        assert_eq!(result.merged.projects[1].efforts[0].effort, 100);
+    }
+}
@@ -0,0 +1,69 @@
+//! Flattened view of the worker assignments stored in the effort grid.
+
+use super::devs::Devs;
+
+/// A single "worker|percent" cell, together with the coordinates it comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub project: i32,
+    pub project_name: String,
+    pub dev: Devs,
+    pub worker: String,
+    pub week: i32,
+    pub percent: i32,
+}
+
+/// Consecutive weeks in which a worker is assigned to the same project and team.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssignmentRun {
+    pub project: i32,
+    pub project_name: String,
+    pub dev: Devs,
+    pub worker: String,
+    /// First week of the run.
+    pub start_week: i32,
+    /// Last week of the run (included).
+    pub end_week: i32,
+    /// Percentage of the first week of the run.
+    pub percent: i32,
+}
+
+/// Merges assignments on consecutive weeks into runs.
+///
+/// With `split_on_percent`, a change of percentage starts a new run, so the
+/// percentage is the same for the whole run.
+/// Runs are sorted by start week, then by project, team and worker.
+pub fn assignment_runs(assignments: &[Assignment], split_on_percent: bool) -> Vec<AssignmentRun> {
+    let mut sorted: Vec<&Assignment> = assignments.iter().collect();
+    sorted.sort_by(|a, b| {
+        (a.project, a.dev, &a.worker, a.week).cmp(&(b.project, b.dev, &b.worker, b.week))
+    });
+
+    let mut runs: Vec<AssignmentRun> = vec![];
+    for a in sorted.into_iter() {
+        if let Some(run) = runs.last_mut()
+            && run.project == a.project
+            && run.dev == a.dev
+            && run.worker == a.worker
+            && a.week <= run.end_week + 7
+            && (!split_on_percent || a.percent == run.percent)
+        {
+            run.end_week = run.end_week.max(a.week);
+            continue;
+        }
+        runs.push(AssignmentRun {
+            project: a.project,
+            project_name: a.project_name.clone(),
+            dev: a.dev,
+            worker: a.worker.clone(),
+            start_week: a.week,
+            end_week: a.week,
+            percent: a.percent,
+        });
+    }
+
+    runs.sort_by(|a, b| {
+        (a.start_week, a.project, a.dev, &a.worker).cmp(&(b.start_week, b.project, b.dev, &b.worker))
+    });
+    runs
+}
@@ -0,0 +1,443 @@
+//! Compact file layout of an effort plan.
+//!
+//! Only what cannot be recomputed is stored: project metadata, the team budgets
+//! that differ from the defaults and one line per non-empty cell. Totals,
+//! remains and over-allocation are rebuilt on load. The output is
+//! deterministic (projects by id, teams in [`Devs::ALL`] order, weeks and rows
+//! ascending), so a change in the plan is a change of a few lines.
+
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::devs::Devs;
+use super::effort_by_date::EffortByDateDto;
+use super::effort_by_dev::EffortByDevDto;
+use super::effort_by_prj::EffortByPrjDto;
+use super::efforts::EffortsDto;
+use super::holiday::{CalendarDto, HolidayDto};
+use super::sovra::LoadTargetDto;
+use super::worker::WorkerDto;
+use crate::date_utils::{DATE_FORMAT, date_text, local_to_days};
+
+/// Value of the `format` field that identifies the compact layout.
+pub const COMPACT_FORMAT: &str = "compact-1";
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+/// A team whose settings differ from a new team.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct CompactTeam {
+    pub team: Devs,
+    #[serde(default)]
+    pub budget: i32,
+    /// Number of worker rows.
+    #[serde(default = "default_rows")]
+    pub rows: i32,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub visible: bool,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub enable: bool,
+}
+
+fn default_rows() -> i32 {
+    1
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct CompactProject {
+    pub id: i32,
+    pub name: String,
+    /// First week, as YYYY-MM-DD.
+    pub start: String,
+    /// Week after the last one, as YYYY-MM-DD.
+    pub end: String,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub visible: bool,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub enable: bool,
+    #[serde(default)]
+    pub teams: Vec<CompactTeam>,
+}
+
+/// The whole plan in compact layout.
+///
+/// Each assignment is a `project;team;week;row;cell` line, e.g.
+/// `0;Mcsw;2026-10-12;0;Mario Rossi|50`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct CompactFile {
+    pub format: String,
+    #[serde(default)]
+    pub worker_names: Vec<String>,
+    #[serde(default)]
+    pub week_off: Vec<i32>,
+    #[serde(default)]
+    pub holidays: Vec<HolidayDto>,
+    #[serde(default)]
+    pub calendar: CalendarDto,
+    #[serde(default)]
+    pub load: LoadTargetDto,
+    #[serde(default)]
+    pub workers: Vec<WorkerDto>,
+    pub projects: Vec<CompactProject>,
+    #[serde(default)]
+    pub assignments: Vec<String>,
+}
+
+/// A value of a compact file that does not fit the plan, with its JSON path,
+/// e.g. `assignments[3]: riga non valida "0;Hw;2026-10-12"`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for CompactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+impl std::error::Error for CompactError {}
+
+/// One non-empty cell, written as a `project;team;week;row;cell` line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactAssignment {
+    pub project: i32,
+    pub team: Devs,
+    /// Monday of the week, in days since 1970-01-01.
+    pub week: i32,
+    pub row: i32,
+    /// Raw cell text, e.g. `Mario Rossi|50`.
+    pub cell: String,
+}
+
+impl CompactAssignment {
+    /// Parses an assignment line, `None` if it is malformed.
+    pub fn parse(line: &str) -> Option<CompactAssignment> {
+        let fields: Vec<&str> = line.splitn(5, ';').collect();
+        let [project, team, week, row, cell] = fields.as_slice() else {
+            return None;
+        };
+        Some(CompactAssignment {
+            project: project.trim().parse().ok()?,
+            team: Devs::from_title(team)?,
+            week: parse_date(week)?,
+            row: row.trim().parse().ok().filter(|r: &i32| *r >= 0)?,
+            cell: cell.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for CompactAssignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{};{:?};{};{};{}",
+            self.project,
+            self.team,
+            date_text(self.week),
+            self.row,
+            self.cell
+        )
+    }
+}
+
+pub(crate) fn parse_date(text: &str) -> Option<i32> {
+    chrono::NaiveDate::parse_from_str(text.trim(), DATE_FORMAT)
+        .ok()
+        .map(|d| local_to_days(&d))
+}
+
+impl From<&EffortsDto> for CompactFile {
+    fn from(efforts: &EffortsDto) -> Self {
+        let mut projects: Vec<&EffortByPrjDto> = efforts.projects.iter().collect();
+        projects.sort_by_key(|p| p.project);
+
+        let mut assignments = vec![];
+        let mut compact_projects = vec![];
+        for prj in projects.into_iter() {
+            let mut teams = vec![];
+            for dev in Devs::ALL.iter() {
+                let Some(team) = prj.efforts.iter().find(|d| d.dev == *dev) else {
+                    continue;
+                };
+                // Older files may have weeks with more rows than `max`
+                let rows = team
+                    .datas
+                    .iter()
+                    .map(|d| d.persons.len() as i32)
+                    .fold(team.max, i32::max);
+                if team.effort != 0 || rows != 1 || !team.visible || !team.enable {
+                    teams.push(CompactTeam {
+                        team: *dev,
+                        budget: team.effort,
+                        rows,
+                        visible: team.visible,
+                        enable: team.enable,
+                    });
+                }
+
+                let mut datas: Vec<&EffortByDateDto> = team.datas.iter().collect();
+                datas.sort_by_key(|d| d.week);
+                for data in datas.into_iter() {
+                    for (row, cell) in data.persons.iter().enumerate() {
+                        if !cell.trim().is_empty() {
+                            assignments.push(
+                                CompactAssignment {
+                                    project: prj.project,
+                                    team: *dev,
+                                    week: data.week,
+                                    row: row as i32,
+                                    cell: cell.trim().to_string(),
+                                }
+                                .to_string(),
+                            );
+                        }
+                    }
+                }
+            }
+
+            compact_projects.push(CompactProject {
+                id: prj.project,
+                name: prj.text.clone(),
+                start: date_text(prj.start_week),
+                end: date_text(prj.end_week),
+                visible: prj.visible,
+                enable: prj.enable,
+                teams,
+            });
+        }
+
+        let mut week_off = efforts.week_off.clone();
+        week_off.sort_unstable();
+        let mut holidays = efforts.holidays.clone();
+        holidays.sort_by(|a, b| (a.day, &a.name).cmp(&(b.day, &b.name)));
+
+        CompactFile {
+            format: COMPACT_FORMAT.to_string(),
+            worker_names: efforts.worker_names.clone(),
+            week_off,
+            holidays,
+            calendar: efforts.calendar.clone(),
+            load: efforts.load,
+            workers: efforts.workers.clone(),
+            projects: compact_projects,
+            assignments,
+        }
+    }
+}
+
+/// Rebuilds the plan; a project date or an assignment line that cannot be
+/// placed is an error rather than being dropped, so that saving the plan does
+/// not lose it.
+impl TryFrom<CompactFile> for EffortsDto {
+    type Error = CompactError;
+
+    fn try_from(file: CompactFile) -> Result<Self, CompactError> {
+        let error = |path: String, message: String| CompactError { path, message };
+        let mut efforts = EffortsDto {
+            sovra: vec![],
+            week_off: file.week_off,
+            holidays: file.holidays,
+            calendar: file.calendar,
+            load: file.load,
+            worker_names: file.worker_names,
+            workers: file.workers,
+            projects: vec![],
+        };
+
+        for (index, prj) in file.projects.into_iter().enumerate() {
+            let date = |field: &str, text: &str| {
+                parse_date(text).ok_or_else(|| {
+                    error(
+                        format!("projects[{}].{}", index, field),
+                        format!("data non valida \"{}\"", text),
+                    )
+                })
+            };
+            let start_week = date("start", &prj.start)?;
+            let end_week = date("end", &prj.end)?;
+            if end_week < start_week {
+                return Err(error(
+                    format!("projects[{}].end", index),
+                    format!("\"{}\" prima dell'inizio \"{}\"", prj.end, prj.start),
+                ));
+            }
+            let efforts_by_dev = Devs::ALL
+                .iter()
+                .map(|dev| {
+                    let team = prj.teams.iter().find(|t| t.team == *dev);
+                    let rows = team.map_or(1, |t| t.rows.max(1));
+                    let budget = team.map_or(0, |t| t.budget);
+                    EffortByDevDto {
+                        project: prj.id,
+                        total: 0,
+                        visible: team.is_none_or(|t| t.visible),
+                        enable: team.is_none_or(|t| t.enable),
+                        dev: *dev,
+                        effort: budget,
+                        remains: 0,
+                        max: rows,
+                        datas: (start_week..end_week)
+                            .step_by(7)
+                            .map(|week| EffortByDateDto {
+                                total: 0,
+                                remains: 0,
+                                dev: *dev,
+                                project: prj.id,
+                                effort: budget,
+                                week,
+                                persons: vec![String::new(); rows as usize],
+                            })
+                            .collect(),
+                    }
+                })
+                .collect();
+
+            efforts.projects.push(EffortByPrjDto {
+                text: prj.name,
+                start_week,
+                end_week,
+                project: prj.id,
+                visible: prj.visible,
+                enable: prj.enable,
+                efforts: efforts_by_dev,
+                source: String::new(),
+            });
+        }
+
+        for (index, line) in file.assignments.iter().enumerate() {
+            let error = |message: &str| {
+                error(
+                    format!("assignments[{}]", index),
+                    format!("{} \"{}\"", message, line),
+                )
+            };
+            let a = CompactAssignment::parse(line).ok_or_else(|| error("riga non valida"))?;
+            let prj = efforts
+                .projects
+                .iter_mut()
+                .find(|p| p.project == a.project)
+                .ok_or_else(|| error("progetto inesistente"))?;
+            let team = prj
+                .efforts
+                .iter_mut()
+                .find(|d| d.dev == a.team)
+                .ok_or_else(|| error("team inesistente"))?;
+            let data = team
+                .datas
+                .iter_mut()
+                .find(|d| d.week == a.week)
+                .ok_or_else(|| error("settimana fuori dal progetto"))?;
+            let row = a.row as usize;
+            if data.persons.len() <= row {
+                data.persons.resize(row + 1, String::new());
+            }
+            data.persons[row] = a.cell;
+            team.max = team.max.max(a.row + 1);
+        }
+
+        // Every week of a team has the same number of rows
+        for prj in efforts.projects.iter_mut() {
+            for team in prj.efforts.iter_mut() {
+                let rows = team.max.max(1) as usize;
+                for data in team.datas.iter_mut() {
+                    data.persons.resize(rows, String::new());
+                }
+            }
+        }
+
+        efforts.rebuild();
+        Ok(efforts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        let team = &mut efforts.projects[0].efforts[3];
+        team.effort = 120;
+        team.max = 2;
+        for data in team.datas.iter_mut() {
+            data.persons = vec![String::new(), String::new()];
+        }
+        team.datas[1].persons = vec!["Mario|50".to_string(), "Anna|25".to_string()];
+        efforts.rebuild();
+        efforts
+    }
+
+    #[test]
+    fn test_only_non_empty_cells() {
+        let file = CompactFile::from(&plan());
+        assert_eq!(file.projects[0].teams.len(), 1);
+        assert_eq!(file.projects[0].teams[0].budget, 120);
+        assert_eq!(file.assignments.len(), 2);
+        assert!(file.assignments[0].starts_with("0;Hw;"));
+        assert!(file.assignments[0].ends_with(";0;Mario|50"));
+        assert!(file.assignments[1].ends_with(";1;Anna|25"));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let original = plan();
+        let json = serde_json::to_string_pretty(&CompactFile::from(&original)).unwrap();
+        let file: CompactFile = serde_json::from_str(&json).unwrap();
+        let loaded = EffortsDto::try_from(file).unwrap();
+
+        let (a, b) = (&original.projects[0], &loaded.projects[0]);
+        assert_eq!((a.start_week, a.end_week), (b.start_week, b.end_week));
+        for (da, db) in a.efforts.iter().zip(b.efforts.iter()) {
+            assert_eq!(da.effort, db.effort);
+            assert_eq!(da.max, db.max);
+            assert_eq!(da.total, db.total);
+            let pa: Vec<_> = da.datas.iter().map(|d| (d.week, d.persons.clone())).collect();
+            let pb: Vec<_> = db.datas.iter().map(|d| (d.week, d.persons.clone())).collect();
+            assert_eq!(pa, pb);
+        }
+        assert_eq!(original.worker_names, loaded.worker_names);
+        assert_eq!(original.sovra.len(), loaded.sovra.len());
+
+        // Same plan, same bytes
+        let again = serde_json::to_string_pretty(&CompactFile::from(&loaded)).unwrap();
+        assert_eq!(json, again);
+    }
+
+    #[test]
+    fn test_unplaceable_values_are_errors() {
+        let file = CompactFile::from(&plan());
+        let week = file.assignments[0].split(';').nth(2).unwrap().to_string();
+
+        let mut bad = file.clone();
+        bad.projects[0].start = "2026-13-01".to_string();
+        let error = EffortsDto::try_from(bad).unwrap_err();
+        assert_eq!(error.path, "projects[0].start");
+
+        for (line, message) in [
+            ("<<<<<<< ours".to_string(), "riga non valida"),
+            (format!("3;Hw;{};0;Lucia|20", week), "progetto inesistente"),
+            (
+                "0;Hw;1999-01-04;0;Lucia|20".to_string(),
+                "settimana fuori dal progetto",
+            ),
+        ] {
+            let mut bad = file.clone();
+            bad.assignments.push(line.clone());
+            assert_eq!(
+                EffortsDto::try_from(bad).unwrap_err().to_string(),
+                format!("assignments[2]: {} \"{}\"", message, line)
+            );
+        }
+    }
+}
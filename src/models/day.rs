@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 pub struct DayDto {
     pub week: i32,
     pub text: String,
+    /// Holidays and closures of the week that apply to everyone.
+    #[serde(default)]
+    pub days_off: i32,
 }
 
 impl DayDto {
@@ -13,6 +16,7 @@ impl DayDto {
             text: crate::date_utils::days_to_local(week)
                 .format("%y-%m-%d")
                 .to_string(),
+            days_off: 0,
         }
     }
 }
@@ -23,6 +27,7 @@ impl From<crate::DayData> for DayDto {
         Self {
             week: d.week,
             text: d.text.to_string(),
+            days_off: d.days_off,
         }
     }
 }
@@ -32,6 +37,7 @@ impl From<DayDto> for crate::DayData {
         Self {
             week: d.week,
             text: d.text.into(),
+            days_off: d.days_off,
         }
     }
 }
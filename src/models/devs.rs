@@ -1,11 +1,14 @@
 //! Development categories and identifiers.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Development categories for effort tracking.
 ///
 /// Represents different teams or roles in the project.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(
+    Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
 pub enum Devs {
     Mcsw = 0,
     Sms = 1,
@@ -18,6 +21,68 @@ pub enum Devs {
     Pjm = 8,
 }
 
+impl Devs {
+    /// All development categories, in display order.
+    pub const ALL: [Devs; 9] = [
+        Devs::Mcsw,
+        Devs::Sms,
+        Devs::Mvh,
+        Devs::Hw,
+        Devs::Ele,
+        Devs::TestHw,
+        Devs::TestFw,
+        Devs::TestSys,
+        Devs::Pjm,
+    ];
+
+    /// Returns the team title shown in the GUI (same as `AppState.model`).
+    pub fn title(&self) -> &'static str {
+        match self {
+            Devs::Mcsw => "Mcsw",
+            Devs::Sms => "Sms",
+            Devs::Mvh => "Mvh",
+            Devs::Hw => "Hw",
+            Devs::Ele => "Ele",
+            Devs::TestHw => "Hw Test",
+            Devs::TestFw => "Fw Test",
+            Devs::TestSys => "Sys Test",
+            Devs::Pjm => "Pjm",
+        }
+    }
+
+    /// Returns the team background colour as `0xRRGGBB` (same as `AppState.model`).
+    pub fn color(&self) -> u32 {
+        match self {
+            Devs::Mcsw => 0x0099ff,
+            Devs::Sms => 0xee82ee,
+            Devs::Mvh => 0xa52a2a,
+            Devs::Hw => 0x008000,
+            Devs::Ele => 0x0000ff,
+            Devs::TestHw => 0xffff00,
+            Devs::TestFw => 0x800080,
+            Devs::TestSys => 0xffa500,
+            Devs::Pjm => 0x00ffff,
+        }
+    }
+
+    /// Returns the colour of the text drawn on [`Devs::color`] as `0xRRGGBB`.
+    pub fn text_color(&self) -> u32 {
+        match self {
+            Devs::Mcsw | Devs::Mvh | Devs::Ele | Devs::TestFw => 0xffffff,
+            _ => 0x000000,
+        }
+    }
+
+    /// Parses a team name, accepting either the GUI title ("Hw Test") or the
+    /// serialized name ("TestHw"). Case and surrounding spaces are ignored.
+    pub fn from_title(text: &str) -> Option<Devs> {
+        let text = text.trim();
+        Devs::ALL.into_iter().find(|dev| {
+            dev.title().eq_ignore_ascii_case(text) || format!("{:?}", dev).eq_ignore_ascii_case(text)
+        })
+    }
+}
+
 impl From<Devs> for i32 {
     fn from(value: Devs) -> Self {
         match value {
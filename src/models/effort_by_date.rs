@@ -1,17 +1,23 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slint::{Model, ModelRc, SharedString};
 use std::collections::HashMap;
 
 use super::devs::Devs;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A week of a team: the workers assigned and the running totals.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct EffortByDateDto {
+    /// Hours consumed up to this week.
     pub total: i32,
     pub remains: i32,
     pub dev: Devs,
     pub project: i32,
+    /// Budget of the team, in hours.
     pub effort: i32,
+    /// Monday of the week, in days since Unix epoch.
     pub week: i32,
+    /// One `worker|percent` cell per row, empty for an empty row.
     pub persons: Vec<String>,
 }
 
@@ -26,6 +32,38 @@ impl EffortByDateDto {
         }
         total
     }
+
+    /// Returns the colour of the running total as `0xRRGGBB`, going from green to red
+    /// as the budget is consumed (same scale as `EffortByDataGui::my-function`).
+    ///
+    /// Returns `None` when nothing has been consumed yet.
+    pub fn consumption_color(&self) -> Option<u32> {
+        const SCALE: [u32; 9] = [
+            0x018730, 0x558d00, 0x6d8800, 0x888800, 0xa88700, 0xb57a00, 0xcf6f00, 0xc54300,
+            0xd52c00,
+        ];
+        if self.total == 0 {
+            return None;
+        }
+        // Slint computes `effort * 10 / 100` in floats
+        let total = self.total as f64;
+        let effort = self.effort as f64;
+        Some(
+            SCALE
+                .iter()
+                .enumerate()
+                .find(|(i, _)| total < effort * ((i + 1) * 10) as f64 / 100.0)
+                .map_or(0xd10100, |(_, color)| *color),
+        )
+    }
+
+    pub fn get_sovra(&self, sovra: &mut HashMap<String, i32>) {
+        for item in self.persons.iter() {
+            if let Some((person, value)) = crate::utils::info_cell(item) {
+                *sovra.entry(person.to_string()).or_insert(0) += value;
+            }
+        }
+    }
 }
 
 // Conversion implementations for EffortByDateData (from Slint)
@@ -85,3 +123,33 @@ impl EffortByDateDataExt for crate::EffortByDateData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn week(total: i32, effort: i32) -> EffortByDateDto {
+        EffortByDateDto {
+            total,
+            remains: effort - total,
+            dev: Devs::Mcsw,
+            project: 0,
+            effort,
+            week: 0,
+            persons: vec![],
+        }
+    }
+
+    #[test]
+    fn test_consumption_color_boundaries() {
+        assert_eq!(week(0, 80).consumption_color(), None);
+        assert_eq!(week(7, 80).consumption_color(), Some(0x018730));
+        // 80 * 10 / 100 == 8: the first step ends before 8
+        assert_eq!(week(8, 80).consumption_color(), Some(0x558d00));
+        // 85 * 10 / 100 == 8.5, as in the GUI
+        assert_eq!(week(8, 85).consumption_color(), Some(0x018730));
+        assert_eq!(week(9, 85).consumption_color(), Some(0x558d00));
+        assert_eq!(week(71, 80).consumption_color(), Some(0xd52c00));
+        assert_eq!(week(72, 80).consumption_color(), Some(0xd10100));
+    }
+}
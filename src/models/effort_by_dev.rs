@@ -1,18 +1,23 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slint::{Model, ModelRc};
 
 use super::devs::Devs;
 use super::effort_by_date::{EffortByDateDataExt, EffortByDateDto};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A team of a project.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct EffortByDevDto {
     pub project: i32,
+    /// Hours consumed.
     pub total: i32,
     pub visible: bool,
     pub enable: bool,
     pub dev: Devs,
+    /// Budget, in hours.
     pub effort: i32,
     pub remains: i32,
+    /// Number of worker rows.
     pub max: i32,
     pub datas: Vec<EffortByDateDto>,
 }
@@ -33,6 +38,28 @@ impl EffortByDevDto {
         }
     }
 
+    /// Whether the team has neither a budget nor any filled cell.
+    pub fn is_empty(&self) -> bool {
+        self.effort == 0
+            && self
+                .datas
+                .iter()
+                .all(|d| d.persons.iter().all(|p| p.trim().is_empty()))
+    }
+
+    /// Recalculates the running totals and remains, like `EffortByDevDataExt::total`.
+    pub fn total(&mut self) {
+        let mut total = 0;
+        for day in self.datas.iter_mut() {
+            total += day.get_total();
+            day.total = total;
+            day.effort = self.effort;
+            day.remains = self.effort - day.total;
+        }
+        self.total = total;
+        self.remains = self.effort - self.total;
+    }
+
     pub fn prepend_weeks(&mut self, weeks: i32, mut start_week: i32) {
         let data = self.datas.first().unwrap().clone();
 
@@ -1,18 +1,28 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slint::{Model, ModelRc, SharedString};
 
 use super::devs::{DevId, Devs};
 use super::effort_by_dev::EffortByDevDto;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A project with its teams.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct EffortByPrjDto {
+    /// Name of the project.
     pub text: String,
+    /// First week, in days since Unix epoch.
     pub start_week: i32,
+    /// Week after the last one, in days since Unix epoch.
     pub end_week: i32,
+    /// Id, the position of the project in the file.
     pub project: i32,
     pub visible: bool,
     pub enable: bool,
     pub efforts: Vec<EffortByDevDto>,
+    /// File the project was loaded from, when it belongs to a workspace.
+    #[serde(default, skip_serializing)]
+    #[schemars(skip)]
+    pub source: String,
 }
 
 impl EffortByPrjDto {
@@ -36,6 +46,18 @@ impl EffortByPrjDto {
                 EffortByDevDto::new(Devs::TestSys, project, num_weeks),
                 EffortByDevDto::new(Devs::Pjm, project, num_weeks),
             ],
+            source: String::new(),
+        }
+    }
+
+    /// Changes the id of the project, in the project and in all its teams and cells.
+    pub fn set_project_id(&mut self, project: i32) {
+        self.project = project;
+        for dev in self.efforts.iter_mut() {
+            dev.project = project;
+            for data in dev.datas.iter_mut() {
+                data.project = project;
+            }
         }
     }
 
@@ -65,6 +87,7 @@ impl From<EffortByPrjDto> for crate::EffortByPrjData {
             start_week: d.start_week,
             end_week: d.end_week,
             text: SharedString::from(d.text.clone()),
+            source: SharedString::from(d.source),
             project: d.project,
             visible: d.visible,
             enable: d.enable,
@@ -88,6 +111,7 @@ impl From<crate::EffortByPrjData> for EffortByPrjDto {
             text: d.text.clone().into(),
             project: d.project,
             efforts: d.efforts.iter().map(EffortByDevDto::from).collect(),
+            source: d.source.into(),
         }
     }
 }
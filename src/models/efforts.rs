@@ -1,14 +1,32 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slint::{Model, ModelRc, SharedString};
+use std::collections::HashMap;
 
+use super::assignment::Assignment;
 use super::effort_by_prj::EffortByPrjDto;
-use super::sovra::SovraDto;
+use super::holiday::{CalendarDto, HolidayDto, effective_load, working_days};
+use super::sovra::{LoadTargetDto, SovraDto};
+use super::worker::WorkerDto;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// The whole plan, as stored in `efforts.json`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct EffortsDto {
+    /// Over-allocation, recomputed on load.
     pub sovra: Vec<SovraDto>,
+    /// Closed weeks, in days since Unix epoch.
     pub week_off: Vec<i32>,
+    #[serde(default)]
+    pub holidays: Vec<HolidayDto>,
+    #[serde(default)]
+    pub calendar: CalendarDto,
+    /// Intended load of the workers without their own.
+    #[serde(default)]
+    pub load: LoadTargetDto,
     pub worker_names: Vec<String>,
+    /// Roster settings, for the workers that have any.
+    #[serde(default)]
+    pub workers: Vec<WorkerDto>,
     pub projects: Vec<EffortByPrjDto>,
 }
 
@@ -28,7 +46,11 @@ impl Default for EffortsDto {
         Self {
             sovra,
             week_off: vec![],
+            holidays: vec![],
+            calendar: CalendarDto::default(),
+            load: LoadTargetDto::default(),
             worker_names: vec![],
+            workers: vec![],
             projects: vec![EffortByPrjDto::new(0)],
         }
     }
@@ -57,6 +79,52 @@ impl EffortsDto {
         }
     }
 
+    /// Recomputes totals, remains, worker list and over-allocation of the whole plan.
+    ///
+    /// This is the same recalculation the GUI performs after each edit: unknown
+    /// workers found in the cells are appended to `worker_names`, and every
+    /// `sovra` week is filled with the sum of the percentages of each worker,
+    /// scaled to the working days left by holidays and closures.
+    pub fn rebuild(&mut self) {
+        for prj in self.projects.iter_mut() {
+            for dev in prj.efforts.iter_mut() {
+                dev.total();
+            }
+        }
+
+        let mut sovra_by_week: HashMap<i32, HashMap<String, i32>> = HashMap::new();
+        for prj in self.projects.iter() {
+            for dev in prj.efforts.iter() {
+                for data in dev.datas.iter() {
+                    data.get_sovra(sovra_by_week.entry(data.week).or_default());
+                }
+            }
+        }
+
+        for a in self.assignments() {
+            if !self.worker_names.contains(&a.worker) {
+                self.worker_names.push(a.worker);
+            }
+        }
+
+        let (start_week, end_week) = self.start_end_weeks();
+        self.sync_sovra(start_week, end_week);
+        for sovra in self.sovra.iter_mut() {
+            let week = sovra_by_week.get(&sovra.week);
+            for (n, name) in self.worker_names.iter().enumerate() {
+                let percent = week.and_then(|w| w.get(name)).copied().unwrap_or(0);
+                let days = working_days(
+                    &self.holidays,
+                    &self.week_off,
+                    &self.calendar,
+                    sovra.week,
+                    name,
+                );
+                sovra.value[n] = effective_load(percent, days);
+            }
+        }
+    }
+
     pub fn start_end_weeks(&self) -> (i32, i32) {
         use chrono::Utc;
 
@@ -79,6 +147,111 @@ impl EffortsDto {
 
         (start_week, end_week)
     }
+
+    /// Flattens every valid, non-empty "worker|percent" cell into an [`Assignment`].
+    ///
+    /// Assignments are returned in file order: project, team, week, row.
+    pub fn assignments(&self) -> Vec<Assignment> {
+        let mut ret = vec![];
+        for prj in self.projects.iter() {
+            for dev in prj.efforts.iter() {
+                for data in dev.datas.iter() {
+                    for person in data.persons.iter() {
+                        if let Some((worker, percent)) = crate::utils::info_cell(person)
+                            && !worker.is_empty()
+                        {
+                            ret.push(Assignment {
+                                project: prj.project,
+                                project_name: prj.text.clone(),
+                                dev: dev.dev,
+                                worker: worker.to_string(),
+                                week: data.week,
+                                percent,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Intended load of a worker: their own or else the one of the plan.
+    pub fn load_target(&self, name: &str) -> LoadTargetDto {
+        self.worker(name).and_then(|w| w.load).unwrap_or(self.load)
+    }
+
+    /// Whether a worker is retired in the given week, see [`WorkerDto::is_retired`].
+    pub fn is_retired(&self, name: &str, week: i32) -> bool {
+        self.worker(name).is_some_and(|w| w.is_retired(week))
+    }
+
+    /// Roster settings of a worker, if any.
+    pub fn worker(&self, name: &str) -> Option<&WorkerDto> {
+        self.workers.iter().find(|w| w.name == name)
+    }
+
+    /// Roster settings of a worker, added if missing.
+    pub fn worker_mut(&mut self, name: &str) -> &mut WorkerDto {
+        let index = match self.workers.iter().position(|w| w.name == name) {
+            Some(index) => index,
+            None => {
+                self.workers.push(WorkerDto::new(name));
+                self.workers.len() - 1
+            }
+        };
+        &mut self.workers[index]
+    }
+
+    /// Renames workers in `worker_names`, in the "worker|percent" cells, in
+    /// the roster settings and in the holidays. Names not in `names` are left
+    /// as they are, and so is the percentage part of the cells.
+    ///
+    /// Call [`rebuild`](Self::rebuild) afterwards if two workers now have the
+    /// same name, so that the over-allocation is summed under one name.
+    pub fn rename_workers(&mut self, names: &HashMap<String, String>) {
+        let rename = |name: &str| names.get(name).cloned();
+
+        let mut worker_names: Vec<String> = vec![];
+        for name in self.worker_names.drain(..) {
+            let name = rename(&name).unwrap_or(name);
+            if !worker_names.contains(&name) {
+                worker_names.push(name);
+            }
+        }
+        self.worker_names = worker_names;
+
+        for prj in self.projects.iter_mut() {
+            for dev in prj.efforts.iter_mut() {
+                for data in dev.datas.iter_mut() {
+                    for person in data.persons.iter_mut() {
+                        if let Some((worker, percent)) = person.split_once('|')
+                            && let Some(name) = rename(worker)
+                        {
+                            *person = format!("{}|{}", name, percent);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut workers: Vec<WorkerDto> = vec![];
+        for mut worker in self.workers.drain(..) {
+            worker.name = rename(&worker.name).unwrap_or(worker.name);
+            if !workers.iter().any(|w| w.name == worker.name) {
+                workers.push(worker);
+            }
+        }
+        self.workers = workers;
+
+        for holiday in self.holidays.iter_mut() {
+            for worker in holiday.workers.iter_mut() {
+                if let Some(name) = rename(worker) {
+                    *worker = name;
+                }
+            }
+        }
+    }
 }
 
 // Conversion implementations for EffortsData (from Slint)
@@ -86,6 +259,14 @@ impl From<EffortsDto> for crate::EffortsData {
     fn from(d: EffortsDto) -> Self {
         Self {
             week_off: ModelRc::new(slint::VecModel::from(d.week_off)),
+            holidays: ModelRc::new(slint::VecModel::from(
+                d.holidays
+                    .into_iter()
+                    .map(crate::HolidayData::from)
+                    .collect::<Vec<_>>(),
+            )),
+            calendar: d.calendar.into(),
+            load: d.load.into(),
             sovra: ModelRc::new(slint::VecModel::from(
                 d.sovra
                     .into_iter()
@@ -98,6 +279,12 @@ impl From<EffortsDto> for crate::EffortsData {
                     .map(SharedString::from)
                     .collect::<Vec<_>>(),
             )),
+            workers: ModelRc::new(slint::VecModel::from(
+                d.workers
+                    .into_iter()
+                    .map(crate::WorkerData::from)
+                    .collect::<Vec<_>>(),
+            )),
             projects: ModelRc::new(slint::VecModel::from(
                 d.projects
                     .into_iter()
@@ -113,7 +300,11 @@ impl From<crate::EffortsData> for EffortsDto {
         Self {
             sovra: d.sovra.iter().map(SovraDto::from).collect(),
             week_off: d.week_off.iter().collect(),
+            holidays: d.holidays.iter().map(HolidayDto::from).collect(),
+            calendar: d.calendar.into(),
+            load: d.load.into(),
             worker_names: d.worker_names.iter().map(|s| s.to_string()).collect(),
+            workers: d.workers.iter().map(WorkerDto::from).collect(),
             projects: d.projects.iter().map(EffortByPrjDto::from).collect(),
         }
     }
@@ -0,0 +1,295 @@
+//! Holidays and closures that reduce the weekly capacity of the workers.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slint::{Model, ModelRc, SharedString};
+
+use crate::date_utils::{days_to_local, local_to_days};
+
+/// A non-working day.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct HolidayDto {
+    /// Day, in days since Unix epoch.
+    pub day: i32,
+    pub name: String,
+    /// Workers affected by the holiday. Empty means everyone.
+    #[serde(default)]
+    pub workers: Vec<String>,
+}
+
+impl HolidayDto {
+    pub fn applies_to(&self, worker: &str) -> bool {
+        self.workers.is_empty() || self.workers.iter().any(|w| w == worker)
+    }
+}
+
+/// Calendar settings of the plan.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct CalendarDto {
+    /// Italian public holidays reduce the working days.
+    #[serde(default)]
+    pub italian_holidays: bool,
+    /// Local patron-saint day ("DD/MM"), added to the Italian public holidays.
+    #[serde(default)]
+    pub patron_saint: Option<String>,
+}
+
+impl CalendarDto {
+    /// Parses a "DD/MM" day into (month, day).
+    pub fn parse_day(text: &str) -> Option<(u32, u32)> {
+        let (day, month) = text.trim().split_once('/')?;
+        let (day, month) = (day.trim().parse().ok()?, month.trim().parse().ok()?);
+        // 2024 is a leap year, so 29/02 is accepted
+        NaiveDate::from_ymd_opt(2024, month, day).map(|_| (month, day))
+    }
+
+    /// Returns the public holidays of a year, sorted by date.
+    ///
+    /// Empty when the Italian public holidays are disabled.
+    pub fn holidays(&self, year: i32) -> Vec<(NaiveDate, String)> {
+        if !self.italian_holidays {
+            return vec![];
+        }
+        let mut days: Vec<(NaiveDate, String)> = italian_holidays(year)
+            .into_iter()
+            .map(|(date, name)| (date, name.to_string()))
+            .collect();
+        if let Some((month, day)) = self.patron_saint.as_deref().and_then(Self::parse_day)
+            && let Some(date) = NaiveDate::from_ymd_opt(year, month, day)
+        {
+            days.push((date, "Santo patrono".to_string()));
+        }
+        days.sort_by_key(|(date, _)| *date);
+        days.dedup_by_key(|(date, _)| *date);
+        days
+    }
+}
+
+/// Returns Easter Sunday of a year (Gregorian calendar).
+///
+/// # Examples
+/// ```
+/// # use project_app::models::holiday::easter_sunday;
+/// # use chrono::NaiveDate;
+/// assert_eq!(easter_sunday(2025), NaiveDate::from_ymd_opt(2025, 4, 20).unwrap());
+/// ```
+pub fn easter_sunday(year: i32) -> NaiveDate {
+    // Anonymous Gregorian algorithm (Meeus/Jones/Butcher)
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).unwrap_or_default()
+}
+
+/// Returns the Italian public holidays of a year, including the Christmas week.
+pub fn italian_holidays(year: i32) -> Vec<(NaiveDate, &'static str)> {
+    const FIXED: [(u32, u32, &str); 10] = [
+        (1, 1, "Capodanno"),
+        (1, 6, "Epifania"),
+        (4, 25, "Festa della Liberazione"),
+        (5, 1, "Festa del Lavoro"),
+        (6, 2, "Festa della Repubblica"),
+        (8, 15, "Ferragosto"),
+        (11, 1, "Ognissanti"),
+        (12, 8, "Immacolata Concezione"),
+        (12, 25, "Natale"),
+        (12, 26, "Santo Stefano"),
+    ];
+
+    let mut days: Vec<(NaiveDate, &'static str)> = FIXED
+        .iter()
+        .filter_map(|(month, day, name)| {
+            NaiveDate::from_ymd_opt(year, *month, *day).map(|date| (date, *name))
+        })
+        .collect();
+    days.push((
+        easter_sunday(year) + Duration::days(1),
+        "Lunedì dell'Angelo",
+    ));
+    for day in [24, 27, 28, 29, 30, 31] {
+        if let Some(date) = NaiveDate::from_ymd_opt(year, 12, day) {
+            days.push((date, "Chiusura natalizia"));
+        }
+    }
+    days.sort_by_key(|(date, _)| *date);
+    days
+}
+
+/// Returns the working days (0-5) of a worker in a week.
+///
+/// Every holiday falling from Monday to Friday removes a day; a week listed
+/// in `week_off` is a full closure.
+///
+/// # Arguments
+/// * `holidays` - Holidays stored in the plan
+/// * `week_off` - Closure weeks (Mondays) stored in the plan
+/// * `calendar` - Public holidays settings
+/// * `week` - Monday of the week, in days since Unix epoch
+/// * `worker` - Worker name; an empty name only matches the holidays of everyone
+pub fn working_days(
+    holidays: &[HolidayDto],
+    week_off: &[i32],
+    calendar: &CalendarDto,
+    week: i32,
+    worker: &str,
+) -> i32 {
+    if week_off.contains(&week) {
+        return 0;
+    }
+    let mut days: Vec<i32> = holidays
+        .iter()
+        .filter(|h| h.day >= week && h.day < week + 5 && h.applies_to(worker))
+        .map(|h| h.day)
+        .collect();
+
+    if calendar.italian_holidays {
+        let mut years = vec![days_to_local(week).year(), days_to_local(week + 4).year()];
+        years.dedup();
+        days.extend(
+            years
+                .into_iter()
+                .flat_map(|year| calendar.holidays(year))
+                .map(|(date, _)| local_to_days(&date))
+                .filter(|day| *day >= week && *day < week + 5),
+        );
+    }
+
+    days.sort_unstable();
+    days.dedup();
+    5 - days.len() as i32
+}
+
+/// Scales the sum of the percentages of a week to the available working days.
+///
+/// A week without working days counts as a single day, so any assignment in a
+/// closure week is reported as heavily over-allocated.
+///
+/// # Examples
+/// ```
+/// # use project_app::models::holiday::effective_load;
+/// assert_eq!(effective_load(80, 5), 80);
+/// assert_eq!(effective_load(80, 4), 100);
+/// assert_eq!(effective_load(0, 0), 0);
+/// ```
+pub fn effective_load(percent: i32, working_days: i32) -> i32 {
+    let days = working_days.max(1);
+    (percent * 5 + days / 2) / days
+}
+
+// Conversion implementations for HolidayData (from Slint)
+impl From<HolidayDto> for crate::HolidayData {
+    fn from(d: HolidayDto) -> Self {
+        Self {
+            day: d.day,
+            name: d.name.into(),
+            workers: ModelRc::new(slint::VecModel::from(
+                d.workers
+                    .into_iter()
+                    .map(SharedString::from)
+                    .collect::<Vec<_>>(),
+            )),
+        }
+    }
+}
+
+impl From<crate::HolidayData> for HolidayDto {
+    fn from(d: crate::HolidayData) -> Self {
+        Self {
+            day: d.day,
+            name: d.name.to_string(),
+            workers: d.workers.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+// Conversion implementations for CalendarData (from Slint)
+impl From<CalendarDto> for crate::CalendarData {
+    fn from(d: CalendarDto) -> Self {
+        Self {
+            italian_holidays: d.italian_holidays,
+            patron_saint: d.patron_saint.unwrap_or_default().into(),
+        }
+    }
+}
+
+impl From<crate::CalendarData> for CalendarDto {
+    fn from(d: crate::CalendarData) -> Self {
+        Self {
+            italian_holidays: d.italian_holidays,
+            patron_saint: Some(d.patron_saint.to_string()).filter(|s| !s.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter_sunday(2024), date(2024, 3, 31));
+        assert_eq!(easter_sunday(2026), date(2026, 4, 5));
+        assert_eq!(easter_sunday(2038), date(2038, 4, 25));
+    }
+
+    #[test]
+    fn test_italian_holidays_with_patron_saint() {
+        let calendar = CalendarDto {
+            italian_holidays: true,
+            patron_saint: Some("24/06".to_string()),
+        };
+        let days = calendar.holidays(2026);
+        assert!(days.contains(&(date(2026, 4, 6), "Lunedì dell'Angelo".to_string())));
+        assert!(days.contains(&(date(2026, 6, 24), "Santo patrono".to_string())));
+        assert!(days.iter().any(|(d, _)| *d == date(2026, 12, 31)));
+        assert!(CalendarDto::default().holidays(2026).is_empty());
+        assert_eq!(CalendarDto::parse_day("31/02"), None);
+    }
+
+    #[test]
+    fn test_working_days() {
+        let calendar = CalendarDto {
+            italian_holidays: true,
+            patron_saint: None,
+        };
+        // Week of Easter Monday 2026
+        let week = local_to_days(&date(2026, 4, 6));
+        assert_eq!(working_days(&[], &[], &calendar, week, "Mario"), 4);
+        assert_eq!(
+            working_days(&[], &[], &CalendarDto::default(), week, "Mario"),
+            5
+        );
+
+        // Christmas week 2026: 24-25 and 28-31 December, then 1 January 2027
+        let week = local_to_days(&date(2026, 12, 21));
+        assert_eq!(working_days(&[], &[], &calendar, week, "Mario"), 3);
+        let week = local_to_days(&date(2026, 12, 28));
+        assert_eq!(working_days(&[], &[], &calendar, week, "Mario"), 0);
+
+        // A stored holiday on the same day does not count twice
+        let week = local_to_days(&date(2026, 4, 6));
+        let holidays = [HolidayDto {
+            day: week,
+            name: "Pasquetta".to_string(),
+            workers: vec!["Mario".to_string()],
+        }];
+        assert_eq!(working_days(&holidays, &[], &calendar, week, "Mario"), 4);
+        assert_eq!(working_days(&holidays, &[week], &calendar, week, ""), 0);
+    }
+}
@@ -6,11 +6,19 @@ pub mod effort_by_date;
 pub mod effort_by_dev;
 pub mod effort_by_prj;
 pub mod efforts;
+pub mod assignment;
+pub mod holiday;
+pub mod compact;
+pub mod worker;
 
 // Re-exports for easier access
 pub use devs::{DevId, Devs, ProjectId};
 pub use day::DayDto;
-pub use sovra::SovraDto;
+pub use sovra::{LoadLevel, LoadTargetDto, SovraDto};
 pub use effort_by_date::EffortByDateDto;
 pub use effort_by_prj::EffortByPrjDto;
 pub use efforts::EffortsDto;
+pub use assignment::{Assignment, AssignmentRun, assignment_runs};
+pub use holiday::{CalendarDto, HolidayDto};
+pub use compact::{COMPACT_FORMAT, CompactError, CompactFile};
+pub use worker::WorkerDto;
@@ -1,12 +1,119 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slint::{Model, ModelRc};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// Load of every worker in a week.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct SovraDto {
+    /// Percent load of each worker, in `worker_names` order.
     pub value: Vec<i32>,
+    /// Monday of the week, in days since Unix epoch.
     pub week: i32,
 }
 
+/// Intended weekly load of a worker, in percent.
+///
+/// The plan has one for everyone and each worker can override it, see
+/// [`EffortsDto::load_target`](super::EffortsDto::load_target).
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadTargetDto {
+    /// Load the worker should have.
+    pub target: i32,
+    /// Load above which the worker is over-allocated.
+    pub max: i32,
+}
+
+impl Default for LoadTargetDto {
+    fn default() -> Self {
+        Self {
+            target: 80,
+            max: 80,
+        }
+    }
+}
+
+impl LoadTargetDto {
+    /// A target with its maximum; the maximum can not be below the target.
+    pub fn new(target: i32, max: i32) -> Result<Self, String> {
+        if target < 0 || max < target {
+            return Err(format!(
+                "Carico non valido: obiettivo {}%, massimo {}%",
+                target, max
+            ));
+        }
+        Ok(Self { target, max })
+    }
+
+    /// Parses `target[/max]`, e.g. `50/60`; the maximum defaults to the target.
+    ///
+    /// ```
+    /// use project_app::models::LoadTargetDto;
+    ///
+    /// assert_eq!(LoadTargetDto::parse("50/60").unwrap().max, 60);
+    /// assert_eq!(LoadTargetDto::parse(" 100 ").unwrap().max, 100);
+    /// assert!(LoadTargetDto::parse("80/50").is_err());
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (target, max) = text.split_once('/').unwrap_or((text, text));
+        match (target.trim().parse(), max.trim().parse()) {
+            (Ok(target), Ok(max)) => Self::new(target, max),
+            _ => Err(format!(
+                "Carico non valido: \"{}\" (formato obiettivo[/massimo])",
+                text
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for LoadTargetDto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.target, self.max)
+    }
+}
+
+/// Load of a worker in a week, as coloured by `RightFooter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadLevel {
+    /// Below half of the target.
+    Low,
+    /// From half of the target up to the target excluded.
+    Partial,
+    /// From the target up to the maximum.
+    Full,
+    /// Above the maximum.
+    Over,
+}
+
+impl LoadLevel {
+    /// Level with the default target: 80%, also the maximum.
+    pub fn from_percent(value: i32) -> Self {
+        Self::from_load(value, &LoadTargetDto::default())
+    }
+
+    /// Level against the intended load of a worker.
+    pub fn from_load(value: i32, load: &LoadTargetDto) -> Self {
+        if value > load.max {
+            LoadLevel::Over
+        } else if value * 2 < load.target {
+            LoadLevel::Low
+        } else if value < load.target {
+            LoadLevel::Partial
+        } else {
+            LoadLevel::Full
+        }
+    }
+
+    /// Returns the footer colour as `0xRRGGBB` (red, yellow, orange, light green).
+    pub fn color(&self) -> u32 {
+        match self {
+            LoadLevel::Over => 0xff0000,
+            LoadLevel::Low => 0xffff00,
+            LoadLevel::Partial => 0xffa500,
+            LoadLevel::Full => 0x90ee90,
+        }
+    }
+}
+
 // Conversion implementations for SovraData (from Slint)
 impl From<SovraDto> for crate::SovraData {
     fn from(d: SovraDto) -> Self {
@@ -25,3 +132,43 @@ impl From<crate::SovraData> for SovraDto {
         }
     }
 }
+
+// Conversion implementations for LoadTargetData (from Slint)
+impl From<LoadTargetDto> for crate::LoadTargetData {
+    fn from(d: LoadTargetDto) -> Self {
+        Self {
+            target: d.target,
+            max: d.max,
+        }
+    }
+}
+
+impl From<crate::LoadTargetData> for LoadTargetDto {
+    fn from(d: crate::LoadTargetData) -> Self {
+        Self {
+            target: d.target,
+            max: d.max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_levels() {
+        // The default keeps the historical footer colours
+        assert_eq!(LoadLevel::from_percent(39), LoadLevel::Low);
+        assert_eq!(LoadLevel::from_percent(40), LoadLevel::Partial);
+        assert_eq!(LoadLevel::from_percent(80), LoadLevel::Full);
+        assert_eq!(LoadLevel::from_percent(81), LoadLevel::Over);
+
+        let part_time = LoadTargetDto::new(50, 60).unwrap();
+        assert_eq!(LoadLevel::from_load(20, &part_time), LoadLevel::Low);
+        assert_eq!(LoadLevel::from_load(40, &part_time), LoadLevel::Partial);
+        assert_eq!(LoadLevel::from_load(60, &part_time), LoadLevel::Full);
+        assert_eq!(LoadLevel::from_load(70, &part_time), LoadLevel::Over);
+        assert!(LoadTargetDto::new(100, 90).is_err());
+    }
+}
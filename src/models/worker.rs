@@ -0,0 +1,72 @@
+//! Roster settings of a worker.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::devs::Devs;
+use super::sovra::LoadTargetDto;
+
+/// Roster settings of a worker, kept in [`EffortsDto::workers`](super::EffortsDto::workers).
+///
+/// Only workers with a setting are listed; the order of the workers is the
+/// one of `worker_names`.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default, PartialEq)]
+pub struct WorkerDto {
+    pub name: String,
+    /// Team the worker belongs to, used to group the footer.
+    #[serde(default)]
+    pub team: Option<Devs>,
+    /// Monday of the first week the worker is no longer in the footer, in days
+    /// since Unix epoch.
+    #[serde(default)]
+    pub retired_from: Option<i32>,
+    /// Intended load, instead of the one of the plan (e.g. part-time workers).
+    #[serde(default)]
+    pub load: Option<LoadTargetDto>,
+}
+
+impl WorkerDto {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether the worker is left out of the footer in the given week.
+    pub fn is_retired(&self, week: i32) -> bool {
+        self.retired_from.is_some_and(|from| from <= week)
+    }
+
+    /// Whether nothing is set, so the entry can be dropped.
+    pub fn is_default(&self) -> bool {
+        self.team.is_none() && self.retired_from.is_none() && self.load.is_none()
+    }
+}
+
+// Conversion implementations for WorkerData (from Slint)
+impl From<WorkerDto> for crate::WorkerData {
+    fn from(d: WorkerDto) -> Self {
+        Self {
+            name: d.name.into(),
+            team: d.team.map_or(-1, i32::from),
+            retired_from: d.retired_from.unwrap_or(-1),
+            target: d.load.map_or(-1, |l| l.target),
+            max: d.load.map_or(-1, |l| l.max),
+        }
+    }
+}
+
+impl From<crate::WorkerData> for WorkerDto {
+    fn from(d: crate::WorkerData) -> Self {
+        Self {
+            name: d.name.to_string(),
+            team: (d.team >= 0).then(|| Devs::from(d.team)),
+            retired_from: (d.retired_from >= 0).then_some(d.retired_from),
+            load: (d.target >= 0).then_some(LoadTargetDto {
+                target: d.target,
+                max: d.max,
+            }),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! JSON Schema of the effort file format.
+//!
+//! The schema is generated from the serde derives of [`EffortsDto`] and the
+//! types it contains, so it always describes what the program reads and
+//! writes. The generated files are shipped in `schema/` for the tools that
+//! produce or consume `efforts.json`; `project_app schema` prints them.
+
+use crate::models::{COMPACT_FORMAT, CompactFile, EffortsDto};
+
+/// Version of the pretty file format described by [`efforts_schema`].
+pub const FORMAT_VERSION: u32 = 1;
+
+fn finish(mut schema: schemars::Schema, title: String) -> serde_json::Value {
+    schema.insert("title".to_string(), title.into());
+    schema.insert(
+        "$comment".to_string(),
+        "Generated by `project_app schema`, do not edit".into(),
+    );
+    schema.to_value()
+}
+
+/// Schema of the pretty layout, the default `efforts.json`.
+pub fn efforts_schema() -> serde_json::Value {
+    finish(
+        schemars::schema_for!(EffortsDto),
+        format!("Effort plan, format {}", FORMAT_VERSION),
+    )
+}
+
+/// Schema of the compact layout, see [`CompactFile`].
+pub fn compact_schema() -> serde_json::Value {
+    finish(
+        schemars::schema_for!(CompactFile),
+        format!("Effort plan, format {}", COMPACT_FORMAT),
+    )
+}
+
+/// The schema as written in `schema/`, with a final newline.
+pub fn render_schema(schema: &serde_json::Value) -> String {
+    let mut json = serde_json::to_string_pretty(schema).unwrap();
+    json.push('\n');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_io::parse_efforts;
+
+    #[test]
+    fn test_shipped_schema_is_current() {
+        // Run `project_app schema --output schema/efforts.schema.json` after changing the format
+        assert_eq!(
+            include_str!("../schema/efforts.schema.json"),
+            render_schema(&efforts_schema())
+        );
+        assert_eq!(
+            include_str!("../schema/efforts-compact.schema.json"),
+            render_schema(&compact_schema())
+        );
+    }
+
+    #[test]
+    fn test_parse_error_has_the_json_path() {
+        let mut efforts = serde_json::to_value(EffortsDto::default()).unwrap();
+        efforts["projects"][0]["efforts"][2]["dev"] = "Fw".into();
+        let error = parse_efforts(&efforts.to_string()).unwrap_err();
+        assert_eq!(error.path().to_string(), "projects[0].efforts[2].dev");
+        assert!(
+            error
+                .to_string()
+                .starts_with("projects[0].efforts[2].dev: unknown variant `Fw`")
+        );
+    }
+}
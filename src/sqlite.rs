@@ -0,0 +1,673 @@
+//! SQLite storage of a plan, for department-wide plans shared by many people.
+//!
+//! The plan is stored in the tables `projects`, `teams`, `budgets`, `workers`
+//! and `assignments` (one row per non-empty cell), plus a `settings` table for
+//! closures, holidays and calendar. Saving compares the plan with the rows as
+//! they were when it was loaded and writes only the differences in a single
+//! transaction, so editing a cell costs one row, whatever the size of the plan,
+//! and the rows changed meanwhile by someone else sharing the database are
+//! left alone. A row changed both here and by someone else is a conflict:
+//! nothing is written and the plan has to be reloaded.
+//!
+//! Available with the `sqlite` feature.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::{Connection, Transaction, TransactionBehavior, params};
+
+use crate::date_utils::date_text;
+use crate::models::compact::{CompactAssignment, CompactProject, CompactTeam};
+use crate::models::{COMPACT_FORMAT, CompactError, CompactFile, Devs, EffortsDto};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS workers (
+    name TEXT PRIMARY KEY,
+    position INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS projects (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    start_week TEXT NOT NULL,
+    end_week TEXT NOT NULL,
+    visible INTEGER NOT NULL DEFAULT 1,
+    enable INTEGER NOT NULL DEFAULT 1
+);
+CREATE TABLE IF NOT EXISTS teams (
+    project INTEGER NOT NULL,
+    team TEXT NOT NULL,
+    rows INTEGER NOT NULL DEFAULT 1,
+    visible INTEGER NOT NULL DEFAULT 1,
+    enable INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (project, team)
+);
+CREATE TABLE IF NOT EXISTS budgets (
+    project INTEGER NOT NULL,
+    team TEXT NOT NULL,
+    hours INTEGER NOT NULL,
+    PRIMARY KEY (project, team)
+);
+CREATE TABLE IF NOT EXISTS assignments (
+    project INTEGER NOT NULL,
+    team TEXT NOT NULL,
+    week TEXT NOT NULL,
+    row INTEGER NOT NULL,
+    cell TEXT NOT NULL,
+    PRIMARY KEY (project, team, week, row)
+);
+";
+
+type ProjectRow = (String, String, String, bool, bool);
+type TeamKey = (i32, String);
+type AssignmentKey = (i32, String, String, i32);
+
+/// The rows of all tables, as read from the database or derived from a plan.
+#[derive(Default, Clone)]
+struct Tables {
+    settings: BTreeMap<String, String>,
+    workers: BTreeMap<String, i32>,
+    projects: BTreeMap<i32, ProjectRow>,
+    teams: BTreeMap<TeamKey, (i32, bool, bool)>,
+    budgets: BTreeMap<TeamKey, i32>,
+    assignments: BTreeMap<AssignmentKey, String>,
+}
+
+impl From<&EffortsDto> for Tables {
+    fn from(efforts: &EffortsDto) -> Self {
+        let file = CompactFile::from(efforts);
+        let mut tables = Tables::default();
+
+        tables.settings.insert(
+            "week_off".to_string(),
+            serde_json::to_string(&file.week_off).unwrap(),
+        );
+        tables.settings.insert(
+            "holidays".to_string(),
+            serde_json::to_string(&file.holidays).unwrap(),
+        );
+        tables.settings.insert(
+            "calendar".to_string(),
+            serde_json::to_string(&file.calendar).unwrap(),
+        );
+        tables.settings.insert(
+            "load".to_string(),
+            serde_json::to_string(&file.load).unwrap(),
+        );
+        tables.settings.insert(
+            "roster".to_string(),
+            serde_json::to_string(&file.workers).unwrap(),
+        );
+        for (position, name) in file.worker_names.iter().enumerate() {
+            tables
+                .workers
+                .entry(name.clone())
+                .or_insert(position as i32);
+        }
+        for prj in file.projects.iter() {
+            tables.projects.insert(
+                prj.id,
+                (
+                    prj.name.clone(),
+                    prj.start.clone(),
+                    prj.end.clone(),
+                    prj.visible,
+                    prj.enable,
+                ),
+            );
+            for team in prj.teams.iter() {
+                let key = (prj.id, format!("{:?}", team.team));
+                if team.rows != 1 || !team.visible || !team.enable {
+                    tables
+                        .teams
+                        .insert(key.clone(), (team.rows, team.visible, team.enable));
+                }
+                if team.budget != 0 {
+                    tables.budgets.insert(key, team.budget);
+                }
+            }
+        }
+        for a in file
+            .assignments
+            .iter()
+            .filter_map(|line| CompactAssignment::parse(line))
+        {
+            tables.assignments.insert(
+                (a.project, format!("{:?}", a.team), date_text(a.week), a.row),
+                a.cell,
+            );
+        }
+        tables
+    }
+}
+
+impl TryFrom<Tables> for EffortsDto {
+    type Error = CompactError;
+
+    fn try_from(tables: Tables) -> Result<Self, CompactError> {
+        let setting = |key: &str| tables.settings.get(key).map(String::as_str).unwrap_or("");
+
+        let mut workers: Vec<(i32, String)> = tables
+            .workers
+            .iter()
+            .map(|(name, position)| (*position, name.clone()))
+            .collect();
+        workers.sort();
+
+        let projects = tables
+            .projects
+            .iter()
+            .map(|(id, (name, start, end, visible, enable))| {
+                let teams = Devs::ALL
+                    .iter()
+                    .filter_map(|dev| {
+                        let key = (*id, format!("{:?}", dev));
+                        let settings = tables.teams.get(&key);
+                        let budget = tables.budgets.get(&key);
+                        if settings.is_none() && budget.is_none() {
+                            return None;
+                        }
+                        let (rows, visible, enable) = settings.copied().unwrap_or((1, true, true));
+                        Some(CompactTeam {
+                            team: *dev,
+                            budget: budget.copied().unwrap_or(0),
+                            rows,
+                            visible,
+                            enable,
+                        })
+                    })
+                    .collect();
+                CompactProject {
+                    id: *id,
+                    name: name.clone(),
+                    start: start.clone(),
+                    end: end.clone(),
+                    visible: *visible,
+                    enable: *enable,
+                    teams,
+                }
+            })
+            .collect();
+
+        let assignments = tables
+            .assignments
+            .iter()
+            .map(|((project, team, week, row), cell)| {
+                format!("{};{};{};{};{}", project, team, week, row, cell)
+            })
+            .collect();
+
+        EffortsDto::try_from(CompactFile {
+            format: COMPACT_FORMAT.to_string(),
+            worker_names: workers.into_iter().map(|(_, name)| name).collect(),
+            week_off: serde_json::from_str(setting("week_off")).unwrap_or_default(),
+            holidays: serde_json::from_str(setting("holidays")).unwrap_or_default(),
+            calendar: serde_json::from_str(setting("calendar")).unwrap_or_default(),
+            load: serde_json::from_str(setting("load")).unwrap_or_default(),
+            workers: serde_json::from_str(setting("roster")).unwrap_or_default(),
+            projects,
+            assignments,
+        })
+    }
+}
+
+/// Keys to delete and rows to write so that `current` becomes `wanted`.
+fn changes<'a, K: Ord, V: PartialEq>(
+    current: &'a BTreeMap<K, V>,
+    wanted: &'a BTreeMap<K, V>,
+) -> (Vec<&'a K>, Vec<(&'a K, &'a V)>) {
+    let deleted = current.keys().filter(|k| !wanted.contains_key(k)).collect();
+    let written = wanted
+        .iter()
+        .filter(|(k, v)| current.get(k) != Some(v))
+        .collect();
+    (deleted, written)
+}
+
+/// Rows changed both from `base` to `wanted` and, differently, from `base` to
+/// `current`, described as `table key`.
+fn conflicts<K: Ord + fmt::Debug, V: PartialEq>(
+    table: &str,
+    base: &BTreeMap<K, V>,
+    current: &BTreeMap<K, V>,
+    wanted: &BTreeMap<K, V>,
+) -> Vec<String> {
+    base.keys()
+        .chain(wanted.keys())
+        .filter(|k| base.get(k) != wanted.get(k))
+        .filter(|k| current.get(k) != base.get(k) && current.get(k) != wanted.get(k))
+        .map(|k| format!("{} {:?}", table, k))
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+impl Tables {
+    /// See [`conflicts`], for all tables.
+    fn conflicts(base: &Tables, current: &Tables, wanted: &Tables) -> Vec<String> {
+        let mut ret = conflicts(
+            "settings",
+            &base.settings,
+            &current.settings,
+            &wanted.settings,
+        );
+        ret.extend(conflicts(
+            "workers",
+            &base.workers,
+            &current.workers,
+            &wanted.workers,
+        ));
+        ret.extend(conflicts(
+            "projects",
+            &base.projects,
+            &current.projects,
+            &wanted.projects,
+        ));
+        ret.extend(conflicts(
+            "teams",
+            &base.teams,
+            &current.teams,
+            &wanted.teams,
+        ));
+        ret.extend(conflicts(
+            "budgets",
+            &base.budgets,
+            &current.budgets,
+            &wanted.budgets,
+        ));
+        ret.extend(conflicts(
+            "assignments",
+            &base.assignments,
+            &current.assignments,
+            &wanted.assignments,
+        ));
+        ret
+    }
+}
+
+/// Error of [`SqliteStore::save`].
+#[derive(Debug)]
+pub enum SaveError {
+    Sqlite(rusqlite::Error),
+    /// Rows changed by someone else since the plan was loaded, that the plan
+    /// changes too. Nothing was written.
+    Conflict(Vec<String>),
+}
+
+impl From<rusqlite::Error> for SaveError {
+    fn from(e: rusqlite::Error) -> Self {
+        SaveError::Sqlite(e)
+    }
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Sqlite(e) => write!(f, "{}", e),
+            SaveError::Conflict(rows) => write!(
+                f,
+                "Righe modificate da un altro utente dopo il caricamento, ricarica il piano: {}",
+                rows.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// A plan stored in an SQLite database.
+pub struct SqliteStore {
+    conn: Connection,
+    /// The rows as loaded or last saved by this store.
+    snapshot: Option<Tables>,
+}
+
+impl SqliteStore {
+    /// Opens the database, creating it and its tables if needed.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the database file
+    pub fn open<P: AsRef<Path>>(path: P) -> rusqlite::Result<SqliteStore> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(SqliteStore {
+            conn,
+            snapshot: None,
+        })
+    }
+
+    /// Loads the whole plan; totals and over-allocation are recomputed.
+    ///
+    /// The rows read are what later saves are compared with.
+    pub fn load(&mut self) -> rusqlite::Result<EffortsDto> {
+        let tables = read_tables(&self.conn)?;
+        self.snapshot = Some(tables.clone());
+        EffortsDto::try_from(tables).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+    }
+
+    /// Saves the plan, writing only the rows it changed since it was loaded
+    /// or last saved by this store. Without a previous load the database is
+    /// made equal to the plan.
+    ///
+    /// # Returns
+    /// * `Ok(n)` - Number of rows inserted, updated or deleted
+    /// * `Err(SaveError::Conflict)` - If someone else changed the same rows
+    pub fn save(&mut self, efforts: &EffortsDto) -> Result<usize, SaveError> {
+        let wanted = Tables::from(efforts);
+
+        // Locks the database for writing before reading the current rows
+        let tx = self
+            .conn
+            .transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let current = read_tables(&tx)?;
+        let base = self.snapshot.as_ref().unwrap_or(&current);
+
+        let conflicts = Tables::conflicts(base, &current, &wanted);
+        if !conflicts.is_empty() {
+            return Err(SaveError::Conflict(conflicts));
+        }
+
+        let count = write_changes(&tx, base, &wanted)?;
+        tx.commit()?;
+        self.snapshot = Some(wanted);
+        Ok(count)
+    }
+}
+
+fn read_tables(conn: &Connection) -> rusqlite::Result<Tables> {
+    let mut tables = Tables::default();
+
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+    for row in stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))? {
+        let (key, value) = row?;
+        tables.settings.insert(key, value);
+    }
+
+    let mut stmt = conn.prepare("SELECT name, position FROM workers")?;
+    for row in stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?)))? {
+        let (name, position) = row?;
+        tables.workers.insert(name, position);
+    }
+
+    let mut stmt =
+        conn.prepare("SELECT id, name, start_week, end_week, visible, enable FROM projects")?;
+    for row in stmt.query_map([], |r| {
+        Ok((
+            r.get(0)?,
+            (r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?),
+        ))
+    })? {
+        let (id, project) = row?;
+        tables.projects.insert(id, project);
+    }
+
+    let mut stmt = conn.prepare("SELECT project, team, rows, visible, enable FROM teams")?;
+    for row in stmt.query_map([], |r| {
+        Ok(((r.get(0)?, r.get(1)?), (r.get(2)?, r.get(3)?, r.get(4)?)))
+    })? {
+        let (key, team) = row?;
+        tables.teams.insert(key, team);
+    }
+
+    let mut stmt = conn.prepare("SELECT project, team, hours FROM budgets")?;
+    for row in stmt.query_map([], |r| Ok(((r.get(0)?, r.get(1)?), r.get(2)?)))? {
+        let (key, hours) = row?;
+        tables.budgets.insert(key, hours);
+    }
+
+    let mut stmt = conn.prepare("SELECT project, team, week, row, cell FROM assignments")?;
+    for row in stmt.query_map([], |r| {
+        Ok(((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?), r.get(4)?))
+    })? {
+        let (key, cell) = row?;
+        tables.assignments.insert(key, cell);
+    }
+
+    Ok(tables)
+}
+
+/// Writes the rows that differ from `base` to `wanted`, leaving the others as
+/// they are in the database.
+fn write_changes(tx: &Transaction, base: &Tables, wanted: &Tables) -> rusqlite::Result<usize> {
+    let mut count = 0;
+
+    let (deleted, written) = changes(&base.settings, &wanted.settings);
+    for key in deleted {
+        count += tx.execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+    }
+    for (key, value) in written {
+        count += tx.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+    }
+
+    let (deleted, written) = changes(&base.workers, &wanted.workers);
+    for name in deleted {
+        count += tx.execute("DELETE FROM workers WHERE name = ?1", params![name])?;
+    }
+    for (name, position) in written {
+        count += tx.execute(
+            "INSERT OR REPLACE INTO workers (name, position) VALUES (?1, ?2)",
+            params![name, position],
+        )?;
+    }
+
+    let (deleted, written) = changes(&base.projects, &wanted.projects);
+    for id in deleted {
+        count += tx.execute("DELETE FROM projects WHERE id = ?1", params![id])?;
+    }
+    for (id, (name, start, end, visible, enable)) in written {
+        count += tx.execute(
+            "INSERT OR REPLACE INTO projects (id, name, start_week, end_week, visible, enable)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, name, start, end, visible, enable],
+        )?;
+    }
+
+    let (deleted, written) = changes(&base.teams, &wanted.teams);
+    for (project, team) in deleted {
+        count += tx.execute(
+            "DELETE FROM teams WHERE project = ?1 AND team = ?2",
+            params![project, team],
+        )?;
+    }
+    for ((project, team), (rows, visible, enable)) in written {
+        count += tx.execute(
+            "INSERT OR REPLACE INTO teams (project, team, rows, visible, enable)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![project, team, rows, visible, enable],
+        )?;
+    }
+
+    let (deleted, written) = changes(&base.budgets, &wanted.budgets);
+    for (project, team) in deleted {
+        count += tx.execute(
+            "DELETE FROM budgets WHERE project = ?1 AND team = ?2",
+            params![project, team],
+        )?;
+    }
+    for ((project, team), hours) in written {
+        count += tx.execute(
+            "INSERT OR REPLACE INTO budgets (project, team, hours) VALUES (?1, ?2, ?3)",
+            params![project, team, hours],
+        )?;
+    }
+
+    let (deleted, written) = changes(&base.assignments, &wanted.assignments);
+    {
+        let mut delete = tx.prepare_cached(
+            "DELETE FROM assignments WHERE project = ?1 AND team = ?2 AND week = ?3 AND row = ?4",
+        )?;
+        for (project, team, week, row) in deleted {
+            count += delete.execute(params![project, team, week, row])?;
+        }
+        let mut insert = tx.prepare_cached(
+            "INSERT OR REPLACE INTO assignments (project, team, week, row, cell)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for ((project, team, week, row), cell) in written {
+            count += insert.execute(params![project, team, week, row, cell])?;
+        }
+    }
+
+    Ok(count)
+}
+
+/// Rows of the databases loaded by [`load_efforts_from_db`], by path, so that
+/// the next [`save_efforts_to_db`] writes only what changed since.
+static SNAPSHOTS: Mutex<BTreeMap<PathBuf, Tables>> = Mutex::new(BTreeMap::new());
+
+fn snapshot_key(path: &str) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// Loads a plan from an SQLite database.
+pub fn load_efforts_from_db(path: &str) -> rusqlite::Result<EffortsDto> {
+    let mut store = SqliteStore::open(path)?;
+    let efforts = store.load()?;
+    if let Some(snapshot) = store.snapshot {
+        SNAPSHOTS
+            .lock()
+            .unwrap()
+            .insert(snapshot_key(path), snapshot);
+    }
+    Ok(efforts)
+}
+
+/// Saves a plan to an SQLite database, writing only what changed since it
+/// was loaded with [`load_efforts_from_db`], see [`SqliteStore::save`].
+pub fn save_efforts_to_db(efforts: &EffortsDto, path: &str) -> Result<usize, SaveError> {
+    let mut store = SqliteStore::open(path)?;
+    let key = snapshot_key(path);
+    store.snapshot = SNAPSHOTS.lock().unwrap().remove(&key);
+    let result = store.save(efforts);
+    if let Some(snapshot) = store.snapshot {
+        SNAPSHOTS.lock().unwrap().insert(key, snapshot);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn plan() -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = "Alpha".to_string();
+        efforts.projects[0].efforts[0].effort = 80;
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|50".to_string()];
+        efforts.projects[0].efforts[0].datas[1].persons = vec!["Lucia|30".to_string()];
+        efforts.week_off = vec![efforts.projects[0].start_week];
+        efforts.rebuild();
+        efforts
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let test_file = "test_store_roundtrip.db";
+        let _ = fs::remove_file(test_file);
+        let original = plan();
+
+        save_efforts_to_db(&original, test_file).unwrap();
+        let loaded = load_efforts_from_db(test_file).unwrap();
+
+        assert_eq!(loaded.projects[0].text, "Alpha");
+        assert_eq!(loaded.projects[0].efforts[0].effort, 80);
+        assert_eq!(
+            loaded.projects[0].efforts[0].total,
+            original.projects[0].efforts[0].total
+        );
+        assert_eq!(
+            loaded.projects[0].efforts[0].datas[1].persons,
+            vec!["Lucia|30"]
+        );
+        assert_eq!(loaded.week_off, original.week_off);
+        assert_eq!(loaded.worker_names, original.worker_names);
+
+        let _ = fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_save_writes_only_changes() {
+        let test_file = "test_store_changes.db";
+        let _ = fs::remove_file(test_file);
+        let mut efforts = plan();
+
+        let mut store = SqliteStore::open(test_file).unwrap();
+        assert!(store.save(&efforts).unwrap() > 5);
+        assert_eq!(store.save(&efforts).unwrap(), 0);
+
+        // One edited cell and one cleared cell
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|60".to_string()];
+        efforts.projects[0].efforts[0].datas[1].persons = vec![String::new()];
+        efforts.rebuild();
+        assert_eq!(store.save(&efforts).unwrap(), 2);
+
+        let loaded = store.load().unwrap();
+        assert_eq!(
+            loaded.projects[0].efforts[0].datas[0].persons,
+            vec!["Mario|60"]
+        );
+        assert_eq!(loaded.worker_names, efforts.worker_names);
+
+        drop(store);
+        let _ = fs::remove_file(test_file);
+    }
+
+    #[test]
+    fn test_two_writers_keep_each_other_edits() {
+        let test_file = "test_store_shared.db";
+        let _ = fs::remove_file(test_file);
+        SqliteStore::open(test_file).unwrap().save(&plan()).unwrap();
+
+        let mut anna = SqliteStore::open(test_file).unwrap();
+        let mut mario = SqliteStore::open(test_file).unwrap();
+        let mut anna_plan = anna.load().unwrap();
+        let mut mario_plan = mario.load().unwrap();
+
+        // Each one edits a different cell
+        anna_plan.projects[0].efforts[0].datas[2].persons = vec!["Anna|40".to_string()];
+        anna_plan.rebuild();
+        mario_plan.projects[0].efforts[0].datas[0].persons = vec!["Mario|70".to_string()];
+        mario_plan.rebuild();
+        anna.save(&anna_plan).unwrap();
+        mario.save(&mario_plan).unwrap();
+
+        let loaded = SqliteStore::open(test_file).unwrap().load().unwrap();
+        let datas = &loaded.projects[0].efforts[0].datas;
+        assert_eq!(datas[0].persons, vec!["Mario|70"]);
+        assert_eq!(datas[2].persons, vec!["Anna|40"]);
+        assert!(loaded.worker_names.contains(&"Anna".to_string()));
+
+        // Both change the same cell: the later save is refused
+        anna_plan.projects[0].efforts[0].datas[1].persons = vec!["Lucia|10".to_string()];
+        mario_plan.projects[0].efforts[0].datas[1].persons = vec!["Lucia|90".to_string()];
+        anna.save(&anna_plan).unwrap();
+        match mario.save(&mario_plan) {
+            Err(SaveError::Conflict(rows)) => assert_eq!(rows.len(), 1),
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+        let loaded = SqliteStore::open(test_file).unwrap().load().unwrap();
+        assert_eq!(
+            loaded.projects[0].efforts[0].datas[1].persons,
+            vec!["Lucia|10"]
+        );
+        // Mario's earlier edit is still there
+        assert_eq!(
+            loaded.projects[0].efforts[0].datas[0].persons,
+            vec!["Mario|70"]
+        );
+
+        drop((anna, mario));
+        let _ = fs::remove_file(test_file);
+    }
+}
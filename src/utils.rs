@@ -1,5 +1,8 @@
 //! Utility functions for calculation and data parsing.
 
+/// Working hours in a full-time week.
+pub const HOURS_PER_WEEK: i32 = 40;
+
 /// Calculates hours from a percentage and total weekly hours.
 ///
 /// # Arguments
@@ -0,0 +1,671 @@
+//! Checks of a loaded plan for data the program would silently misread.
+//!
+//! The loader accepts anything that matches the file format, so cells like
+//! `Bob|abc` (counted as 0%), names differing only by case, weeks that are not
+//! Mondays or ids that do not match their project go unnoticed. [`validate`]
+//! lists them with their position in the plan and [`fix`] corrects the ones
+//! that can be corrected without guessing.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use crate::date_utils::date_text;
+use crate::models::{Devs, EffortsDto, LoadTargetDto};
+
+/// What is wrong.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// The percentage is not a number, e.g. `Bob|abc`; it counts as 0%.
+    UnparsablePercent { cell: String },
+    /// Not in `worker|percent` form, or without a worker.
+    MalformedCell { cell: String },
+    /// Negative or over 100%.
+    PercentOutOfRange { worker: String, percent: i32 },
+    /// Same worker as `canonical` apart from case or spaces.
+    NameVariant { name: String, canonical: String },
+    /// The week does not start on Monday.
+    WeekNotMonday { week: i32 },
+    /// The week does not follow the previous one by 7 days.
+    WeekGap { week: i32, previous: i32 },
+    /// A team or cell with the id of another project.
+    ProjectIdMismatch { found: i32, expected: i32 },
+    /// A cell with the team of another row.
+    TeamMismatch { found: Devs, expected: Devs },
+    /// An over-allocation week with a value count other than the workers.
+    SovraLength {
+        week: i32,
+        found: usize,
+        expected: usize,
+    },
+    /// A maximum load below the target, of a worker or of the whole plan.
+    InvalidLoad {
+        worker: Option<String>,
+        target: i32,
+        max: i32,
+    },
+}
+
+fn is_monday(days: i32) -> bool {
+    NaiveDate::from_epoch_days(days).is_some_and(|d| d.weekday() == Weekday::Mon)
+}
+
+/// Monday of the week containing the day.
+fn monday(days: i32) -> i32 {
+    NaiveDate::from_epoch_days(days)
+        .map_or(days, |d| days - d.weekday().num_days_from_monday() as i32)
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::UnparsablePercent { cell } => {
+                write!(f, "percentuale non valida in \"{}\" (vale 0%)", cell)
+            }
+            Issue::MalformedCell { cell } => {
+                write!(f, "cella \"{}\" non nel formato worker|percentuale", cell)
+            }
+            Issue::PercentOutOfRange { worker, percent } => {
+                write!(f, "{}: percentuale {}% fuori da 0-100", worker, percent)
+            }
+            Issue::NameVariant { name, canonical } => write!(
+                f,
+                "\"{}\" differisce da \"{}\" solo per maiuscole o spazi",
+                name, canonical
+            ),
+            Issue::WeekNotMonday { week } => {
+                write!(f, "la settimana {} non inizia di lunedì", date_text(*week))
+            }
+            Issue::WeekGap { week, previous } => write!(
+                f,
+                "la settimana {} segue {} di {} giorni invece di 7",
+                date_text(*week),
+                date_text(*previous),
+                week - previous
+            ),
+            Issue::ProjectIdMismatch { found, expected } => {
+                write!(f, "id progetto {} invece di {}", found, expected)
+            }
+            Issue::TeamMismatch { found, expected } => {
+                write!(f, "team {} invece di {}", found.title(), expected.title())
+            }
+            Issue::SovraLength {
+                week,
+                found,
+                expected,
+            } => write!(
+                f,
+                "sovrallocazione della settimana {}: {} valori invece di {} (uno per worker)",
+                date_text(*week),
+                found,
+                expected
+            ),
+            Issue::InvalidLoad {
+                worker,
+                target,
+                max,
+            } => {
+                let of = worker
+                    .as_ref()
+                    .map_or("del piano".to_string(), |w| format!("di {}", w));
+                if *target < 0 {
+                    write!(f, "carico {}: obiettivo {}% negativo", of, target)
+                } else {
+                    write!(
+                        f,
+                        "carico {}: obiettivo {}% oltre il massimo {}%",
+                        of, target, max
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// A problem and where it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// Position of the project in `projects`.
+    pub project: Option<usize>,
+    pub project_name: String,
+    pub dev: Option<Devs>,
+    pub week: Option<i32>,
+    pub issue: Issue,
+    /// Whether [`fix`] corrects it.
+    pub fixable: bool,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut place = vec![];
+        if self.project.is_some() {
+            place.push(self.project_name.clone());
+        }
+        if let Some(dev) = self.dev {
+            place.push(dev.title().to_string());
+        }
+        if let Some(week) = self.week {
+            place.push(date_text(week));
+        }
+        if !place.is_empty() {
+            write!(f, "{}: ", place.join(" / "))?;
+        }
+        write!(f, "{}", self.issue)?;
+        if self.fixable {
+            write!(f, " [correggibile]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of [`validate`].
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub problems: Vec<Problem>,
+}
+
+impl ValidationReport {
+    pub fn is_empty(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn fixable(&self) -> usize {
+        self.problems.iter().filter(|p| p.fixable).count()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Nessun problema");
+        }
+        for problem in self.problems.iter() {
+            writeln!(f, "{}", problem)?;
+        }
+        writeln!(
+            f,
+            "Problemi: {}, correggibili automaticamente: {}",
+            self.problems.len(),
+            self.fixable()
+        )
+    }
+}
+
+/// What is wrong with a single grid cell, see [`check_cell`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellProblem {
+    /// Not in `worker|percent` form, or without a worker; it counts as 0%.
+    Malformed,
+    /// The percentage is not a number; it counts as 0%.
+    UnparsablePercent,
+    /// Negative or over 100%.
+    PercentOutOfRange(i32),
+    /// A worker that is not in `worker_names`.
+    UnknownWorker,
+}
+
+impl CellProblem {
+    /// Whether the cell is read as 0%, whatever it looks like.
+    pub fn is_unreadable(&self) -> bool {
+        matches!(
+            self,
+            CellProblem::Malformed | CellProblem::UnparsablePercent
+        )
+    }
+
+    /// Explanation shown on the cell in the grid.
+    pub fn tooltip(&self) -> String {
+        match self {
+            CellProblem::Malformed => {
+                "Non nella forma worker|percentuale: conta come 0%".to_string()
+            }
+            CellProblem::UnparsablePercent => {
+                "La percentuale non è un numero: conta come 0%".to_string()
+            }
+            CellProblem::PercentOutOfRange(percent) => {
+                format!("{}% fuori dall'intervallo 0-100", percent)
+            }
+            CellProblem::UnknownWorker => "Worker non presente nell'elenco".to_string(),
+        }
+    }
+}
+
+/// Checks a "worker|percent" cell; empty cells are fine.
+///
+/// The percent is read as [`crate::utils::info_cell`] reads it for the totals,
+/// so `Mario|50 ` with a trailing space is unparsable.
+///
+/// # Arguments
+/// * `cell` - The cell text
+/// * `is_known` - Whether a worker is in `worker_names`
+pub fn check_cell(cell: &str, is_known: impl Fn(&str) -> bool) -> Option<CellProblem> {
+    if cell.trim().is_empty() {
+        return None;
+    }
+    match cell.split('|').collect::<Vec<_>>().as_slice() {
+        [worker, _] if worker.trim().is_empty() => Some(CellProblem::Malformed),
+        [worker, percent] => match percent.parse::<i32>() {
+            Err(_) => Some(CellProblem::UnparsablePercent),
+            Ok(percent) if !(0..=100).contains(&percent) => {
+                Some(CellProblem::PercentOutOfRange(percent))
+            }
+            Ok(_) if !is_known(worker) => Some(CellProblem::UnknownWorker),
+            Ok(_) => None,
+        },
+        _ => Some(CellProblem::Malformed),
+    }
+}
+
+/// Name used to recognise the same worker written differently.
+pub(crate) fn name_key(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Name variants mapped to the first spelling in `worker_names`.
+fn name_variants(efforts: &EffortsDto) -> HashMap<String, String> {
+    let mut canonical: HashMap<String, &String> = HashMap::new();
+    let mut variants = HashMap::new();
+    for name in efforts.worker_names.iter() {
+        match canonical.get(&name_key(name)) {
+            Some(first) => {
+                variants.insert(name.clone(), (*first).clone());
+            }
+            None => {
+                canonical.insert(name_key(name), name);
+            }
+        }
+    }
+    for a in efforts.assignments() {
+        if let Some(first) = canonical.get(&name_key(&a.worker))
+            && **first != a.worker
+        {
+            variants.insert(a.worker, (*first).clone());
+        }
+    }
+    variants
+}
+
+/// Whether consecutive weeks are 7 days apart, so that moving them all to
+/// Monday keeps every cell in its week.
+fn evenly_spaced(weeks: &[i32]) -> bool {
+    weeks.windows(2).all(|w| w[1] - w[0] == 7)
+}
+
+/// Checks the plan.
+///
+/// # Arguments
+/// * `efforts` - The plan as loaded, before any recalculation
+pub fn validate(efforts: &EffortsDto) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let variants = name_variants(efforts);
+
+    for name in efforts.worker_names.iter() {
+        if let Some(first) = variants.get(name) {
+            report.problems.push(Problem {
+                project: None,
+                project_name: String::new(),
+                dev: None,
+                week: None,
+                issue: Issue::NameVariant {
+                    name: name.clone(),
+                    canonical: first.clone(),
+                },
+                fixable: true,
+            });
+        }
+    }
+
+    for (index, prj) in efforts.projects.iter().enumerate() {
+        let mut push = |dev: Option<Devs>, week: Option<i32>, issue: Issue, fixable: bool| {
+            report.problems.push(Problem {
+                project: Some(index),
+                project_name: prj.text.clone(),
+                dev,
+                week,
+                issue,
+                fixable,
+            });
+        };
+
+        if !is_monday(prj.start_week) {
+            push(
+                None,
+                None,
+                Issue::WeekNotMonday {
+                    week: prj.start_week,
+                },
+                true,
+            );
+        }
+
+        for dev in prj.efforts.iter() {
+            if dev.project != prj.project {
+                push(
+                    Some(dev.dev),
+                    None,
+                    Issue::ProjectIdMismatch {
+                        found: dev.project,
+                        expected: prj.project,
+                    },
+                    true,
+                );
+            }
+
+            let weeks: Vec<i32> = dev.datas.iter().map(|d| d.week).collect();
+            let movable = evenly_spaced(&weeks);
+            for (i, data) in dev.datas.iter().enumerate() {
+                let week = Some(data.week);
+                if !is_monday(data.week) {
+                    push(
+                        Some(dev.dev),
+                        week,
+                        Issue::WeekNotMonday { week: data.week },
+                        movable,
+                    );
+                }
+                if i > 0 && data.week - weeks[i - 1] != 7 {
+                    push(
+                        Some(dev.dev),
+                        week,
+                        Issue::WeekGap {
+                            week: data.week,
+                            previous: weeks[i - 1],
+                        },
+                        false,
+                    );
+                }
+                if data.project != prj.project && dev.project == prj.project {
+                    push(
+                        Some(dev.dev),
+                        week,
+                        Issue::ProjectIdMismatch {
+                            found: data.project,
+                            expected: prj.project,
+                        },
+                        true,
+                    );
+                }
+                if data.dev != dev.dev {
+                    push(
+                        Some(dev.dev),
+                        week,
+                        Issue::TeamMismatch {
+                            found: data.dev,
+                            expected: dev.dev,
+                        },
+                        true,
+                    );
+                }
+
+                for cell in data.persons.iter() {
+                    let worker = cell.split('|').next().unwrap_or_default();
+                    let issue = match check_cell(cell, |_| true) {
+                        Some(CellProblem::Malformed) => Issue::MalformedCell { cell: cell.clone() },
+                        Some(CellProblem::UnparsablePercent) => {
+                            Issue::UnparsablePercent { cell: cell.clone() }
+                        }
+                        Some(CellProblem::PercentOutOfRange(percent)) => Issue::PercentOutOfRange {
+                            worker: worker.to_string(),
+                            percent,
+                        },
+                        _ => match variants.get(worker) {
+                            Some(canonical) if !cell.trim().is_empty() => Issue::NameVariant {
+                                name: worker.to_string(),
+                                canonical: canonical.clone(),
+                            },
+                            _ => continue,
+                        },
+                    };
+                    let fixable = matches!(issue, Issue::NameVariant { .. });
+                    push(Some(dev.dev), week, issue, fixable);
+                }
+            }
+        }
+    }
+
+    let expected = efforts.worker_names.len();
+    for sovra in efforts.sovra.iter() {
+        if sovra.value.len() != expected {
+            report.problems.push(Problem {
+                project: None,
+                project_name: String::new(),
+                dev: None,
+                week: Some(sovra.week),
+                issue: Issue::SovraLength {
+                    week: sovra.week,
+                    found: sovra.value.len(),
+                    expected,
+                },
+                fixable: true,
+            });
+        }
+    }
+
+    let loads = std::iter::once((None, efforts.load)).chain(
+        efforts
+            .workers
+            .iter()
+            .filter_map(|w| w.load.map(|load| (Some(w.name.clone()), load))),
+    );
+    for (worker, load) in loads {
+        if LoadTargetDto::new(load.target, load.max).is_err() {
+            report.problems.push(Problem {
+                project: None,
+                project_name: String::new(),
+                dev: None,
+                week: None,
+                issue: Issue::InvalidLoad {
+                    worker,
+                    target: load.target,
+                    max: load.max,
+                },
+                fixable: false,
+            });
+        }
+    }
+
+    report
+}
+
+/// Corrects the problems of [`validate`] marked as fixable, then recomputes
+/// totals and over-allocation.
+///
+/// Name variants take the first spelling in `worker_names`, ids and teams take
+/// those of the row they are in, and weeks are moved to their Monday when the
+/// whole row can be moved together. Invalid percentages and gaps between
+/// weeks are left for the user.
+///
+/// # Returns
+/// The number of problems corrected.
+pub fn fix(efforts: &mut EffortsDto) -> usize {
+    let fixed = validate(efforts).fixable();
+    if fixed == 0 {
+        return 0;
+    }
+
+    efforts.rename_workers(&name_variants(efforts));
+
+    for prj in efforts.projects.iter_mut() {
+        let id = prj.project;
+        prj.set_project_id(id);
+
+        let shift = prj.start_week - monday(prj.start_week);
+        prj.start_week -= shift;
+        prj.end_week -= shift;
+
+        for dev in prj.efforts.iter_mut() {
+            let weeks: Vec<i32> = dev.datas.iter().map(|d| d.week).collect();
+            let movable = evenly_spaced(&weeks);
+            for data in dev.datas.iter_mut() {
+                data.dev = dev.dev;
+                if movable {
+                    data.week = monday(data.week);
+                }
+            }
+        }
+    }
+
+    efforts.rebuild();
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(efforts: &EffortsDto) -> Vec<Issue> {
+        validate(efforts)
+            .problems
+            .into_iter()
+            .map(|p| p.issue)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_plan() {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|50".to_string()];
+        efforts.rebuild();
+        assert!(validate(&efforts).is_empty());
+    }
+
+    #[test]
+    fn test_cells_and_names() {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].efforts[0].datas[0].persons = vec![
+            "Bob|abc".to_string(),
+            "Mario Rossi|120".to_string(),
+            "Anna".to_string(),
+            "mario  rossi|10".to_string(),
+        ];
+        efforts.worker_names = vec!["Mario Rossi".to_string()];
+        let (start_week, end_week) = efforts.start_end_weeks();
+        efforts.sync_sovra(start_week, end_week);
+
+        assert_eq!(
+            issues(&efforts),
+            vec![
+                Issue::UnparsablePercent {
+                    cell: "Bob|abc".to_string()
+                },
+                Issue::PercentOutOfRange {
+                    worker: "Mario Rossi".to_string(),
+                    percent: 120
+                },
+                Issue::MalformedCell {
+                    cell: "Anna".to_string()
+                },
+                Issue::NameVariant {
+                    name: "mario  rossi".to_string(),
+                    canonical: "Mario Rossi".to_string()
+                },
+            ]
+        );
+
+        assert_eq!(fix(&mut efforts), 1);
+        assert_eq!(
+            efforts.projects[0].efforts[0].datas[0].persons[3],
+            "Mario Rossi|10"
+        );
+        // Only the percentages are left for the user
+        assert_eq!(validate(&efforts).problems.len(), 3);
+    }
+
+    #[test]
+    fn test_check_cell() {
+        let known = |name: &str| name == "Mario";
+        assert_eq!(check_cell("", known), None);
+        assert_eq!(check_cell("Mario|50", known), None);
+        assert_eq!(check_cell("Mario", known), Some(CellProblem::Malformed));
+        assert_eq!(check_cell("Mario|5|0", known), Some(CellProblem::Malformed));
+        assert_eq!(check_cell("|50", known), Some(CellProblem::Malformed));
+        assert_eq!(
+            check_cell("Mario|abc", known),
+            Some(CellProblem::UnparsablePercent)
+        );
+        assert_eq!(
+            check_cell("Mario|50 ", known),
+            Some(CellProblem::UnparsablePercent)
+        );
+        assert_eq!(crate::utils::info_cell("Mario|50 "), Some(("Mario", 0)));
+        assert_eq!(
+            check_cell("Mario|50 ", known).unwrap().tooltip(),
+            "La percentuale non è un numero: conta come 0%"
+        );
+        assert_eq!(
+            check_cell("Mario|150", known),
+            Some(CellProblem::PercentOutOfRange(150))
+        );
+        assert_eq!(
+            check_cell("Mario Rosi|50", known),
+            Some(CellProblem::UnknownWorker)
+        );
+        assert!(CellProblem::UnparsablePercent.is_unreadable());
+        assert!(!CellProblem::UnknownWorker.is_unreadable());
+    }
+
+    #[test]
+    fn test_structure_fixed() {
+        let mut efforts = EffortsDto::default();
+        efforts.rebuild();
+        let prj = &mut efforts.projects[0];
+        prj.efforts[1].project = 4;
+        prj.efforts[2].datas[3].dev = Devs::Pjm;
+        for data in prj.efforts[3].datas.iter_mut() {
+            data.week += 2;
+        }
+        prj.efforts[4].datas[5].week += 7;
+        efforts.sovra[0].value = vec![10];
+
+        let report = validate(&efforts);
+        let kinds: Vec<(Option<Devs>, bool)> =
+            report.problems.iter().map(|p| (p.dev, p.fixable)).collect();
+        assert!(kinds.contains(&(Some(Devs::Sms), true)));
+        assert!(kinds.contains(&(Some(Devs::Mvh), true)));
+        assert!(kinds.contains(&(Some(Devs::Hw), true)));
+        assert!(kinds.contains(&(None, true)));
+        // A week out of sequence cannot be moved without moving its cells
+        assert!(report.problems.iter().any(|p| p.dev == Some(Devs::Ele)
+            && !p.fixable
+            && matches!(p.issue, Issue::WeekGap { .. })));
+
+        fix(&mut efforts);
+        let left = validate(&efforts);
+        assert!(left.problems.iter().all(|p| p.dev == Some(Devs::Ele)));
+        assert_eq!(left.fixable(), 0);
+    }
+
+    #[test]
+    fn test_invalid_load() {
+        let mut efforts = EffortsDto::default();
+        efforts.rebuild();
+        efforts.load.max = 60;
+        efforts.worker_mut("Anna").load = Some(LoadTargetDto::new(50, 60).unwrap());
+        assert_eq!(
+            validate(&efforts)
+                .problems
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>(),
+            vec!["carico del piano: obiettivo 80% oltre il massimo 60%"]
+        );
+
+        efforts.load.max = 80;
+        efforts.worker_mut("Anna").load = Some(LoadTargetDto {
+            target: -10,
+            max: 60,
+        });
+        assert_eq!(
+            validate(&efforts)
+                .problems
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>(),
+            vec!["carico di Anna: obiettivo -10% negativo"]
+        );
+    }
+}
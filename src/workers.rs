@@ -0,0 +1,528 @@
+//! Worker names as typed in the cells.
+//!
+//! Any name that is not in `worker_names` becomes a new worker, so a typo like
+//! "Mario Rosi" silently splits someone's load in two. These helpers back the
+//! cell editor: [`suggest_workers`] completes what is being typed and
+//! [`similar_workers`] finds the known names a new one probably meant.
+//! [`rename_worker`] fixes the names already in the plan, and the roster
+//! functions ([`roster`], [`add_worker`], [`purge_unused`]…) maintain the list
+//! of workers shown in the footer.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::models::{Devs, EffortsDto, LoadTargetDto};
+use crate::validate::name_key;
+
+/// Most names listed by [`suggest_workers`].
+pub const MAX_SUGGESTIONS: usize = 8;
+
+/// Least [Jaro-Winkler](strsim::jaro_winkler) similarity for
+/// [`similar_workers`].
+const SIMILARITY: f64 = 0.85;
+
+/// Known workers matching the name part of a cell being typed.
+///
+/// Names where a word starts with the typed text come first, then names
+/// containing it anywhere; the match ignores case. With nothing typed every
+/// worker is listed, and a name already typed in full lists nothing.
+///
+/// # Examples
+/// ```
+/// # use project_app::workers::suggest_workers;
+/// let known = ["Mario Rossi", "Anna Verdi", "Lucia Bianchi"];
+/// assert_eq!(suggest_workers("ro|50", known), vec!["Mario Rossi"]);
+/// assert_eq!(suggest_workers("an", known), vec!["Anna Verdi", "Lucia Bianchi"]);
+/// ```
+pub fn suggest_workers<S: AsRef<str>>(
+    cell: &str,
+    known: impl IntoIterator<Item = S>,
+) -> Vec<String> {
+    let typed = cell.split('|').next().unwrap_or_default();
+    let key = name_key(typed);
+
+    let mut starts = vec![];
+    let mut contains = vec![];
+    for name in known {
+        let name = name.as_ref();
+        if name == typed {
+            return vec![];
+        }
+        let name_lower = name.to_lowercase();
+        if name_lower.split_whitespace().any(|w| w.starts_with(&key))
+            || name_lower.starts_with(&key)
+        {
+            starts.push(name.to_string());
+        } else if name_lower.contains(&key) {
+            contains.push(name.to_string());
+        }
+    }
+    starts.extend(contains);
+    starts.truncate(MAX_SUGGESTIONS);
+    starts
+}
+
+/// Known workers that look like `name`, most similar first.
+///
+/// Used before adding a new worker, to ask "did you mean…". Spelling
+/// differences in case and spaces only count as the same name.
+///
+/// # Examples
+/// ```
+/// # use project_app::workers::similar_workers;
+/// let known = ["Mario Rossi", "Anna Verdi"];
+/// assert_eq!(similar_workers("Mario Rosi", known), vec!["Mario Rossi"]);
+/// assert!(similar_workers("Paolo Neri", known).is_empty());
+/// ```
+pub fn similar_workers<S: AsRef<str>>(
+    name: &str,
+    known: impl IntoIterator<Item = S>,
+) -> Vec<String> {
+    let key = name_key(name);
+    let mut scored: Vec<(f64, String)> = known
+        .into_iter()
+        .filter(|k| k.as_ref() != name)
+        .map(|k| {
+            (
+                strsim::jaro_winkler(&key, &name_key(k.as_ref())),
+                k.as_ref().to_string(),
+            )
+        })
+        .filter(|(score, _)| *score >= SIMILARITY)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Replaces the worker or the percentage of a "worker|percent" cell, keeping
+/// the other part as it is.
+///
+/// # Examples
+/// ```
+/// # use project_app::workers::edit_cell;
+/// assert_eq!(edit_cell("Mar|50", Some("Mario Rossi"), None), "Mario Rossi|50");
+/// assert_eq!(edit_cell("Mario Rossi", None, Some(25)), "Mario Rossi|25");
+/// assert_eq!(edit_cell("", Some("Anna"), None), "Anna|");
+/// ```
+pub fn edit_cell(cell: &str, worker: Option<&str>, percent: Option<i32>) -> String {
+    let (old_worker, old_percent) = cell.split_once('|').unwrap_or((cell, ""));
+    format!(
+        "{}|{}",
+        worker.unwrap_or(old_worker),
+        percent.map_or(old_percent.to_string(), |p| p.to_string())
+    )
+}
+
+/// Outcome of [`rename_worker`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenameReport {
+    pub from: String,
+    pub to: String,
+    /// `to` was already a worker: the two are now one.
+    pub merged: bool,
+    /// Cells rewritten.
+    pub cells: usize,
+}
+
+impl fmt::Display for RenameReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "\"{}\" {} \"{}\": celle modificate: {}",
+            self.from,
+            if self.merged {
+                "unito a"
+            } else {
+                "rinominato in"
+            },
+            self.to,
+            self.cells
+        )
+    }
+}
+
+/// Renames a worker everywhere in the plan: cells, `worker_names`, roster
+/// settings and holidays. When `to` is already a worker the two are merged and
+/// `to` keeps its place in the over-allocation columns and its settings.
+///
+/// Totals and over-allocation are recomputed afterwards.
+///
+/// # Returns
+/// * `Ok(RenameReport)` - What was changed
+/// * `Err(String)` - If `from` is not in the plan or `to` is not a valid name
+pub fn rename_worker(
+    efforts: &mut EffortsDto,
+    from: &str,
+    to: &str,
+) -> Result<RenameReport, String> {
+    let to = to.trim();
+    if to.is_empty() || to.contains('|') {
+        return Err(format!("Nome non valido: \"{}\"", to));
+    }
+    if from == to {
+        return Err(format!("\"{}\" ha già questo nome", from));
+    }
+
+    let assignments = efforts.assignments();
+    let is_worker = |name: &str| {
+        efforts.worker_names.iter().any(|n| n == name)
+            || assignments.iter().any(|a| a.worker == name)
+    };
+    if !is_worker(from) {
+        return Err(format!("Worker non trovato: \"{}\"", from));
+    }
+    let merged = is_worker(to);
+
+    let cells = efforts
+        .projects
+        .iter()
+        .flat_map(|prj| prj.efforts.iter())
+        .flat_map(|dev| dev.datas.iter())
+        .flat_map(|data| data.persons.iter())
+        .filter(|p| p.split_once('|').is_some_and(|(worker, _)| worker == from))
+        .count();
+
+    if merged {
+        efforts.worker_names.retain(|n| n != from);
+        efforts.workers.retain(|w| w.name != from);
+    }
+    efforts.rename_workers(&HashMap::from([(from.to_string(), to.to_string())]));
+    if merged {
+        // A cell that held both names keeps one entry with the sum
+        for data in efforts
+            .projects
+            .iter_mut()
+            .flat_map(|prj| prj.efforts.iter_mut())
+            .flat_map(|dev| dev.datas.iter_mut())
+        {
+            let percent = |p: &str| {
+                p.split_once('|')
+                    .filter(|(worker, _)| *worker == to)
+                    .and_then(|(_, percent)| percent.trim().parse::<i32>().ok())
+            };
+            if data.persons.iter().filter(|p| percent(p).is_some()).count() < 2 {
+                continue;
+            }
+            let total: i32 = data.persons.iter().filter_map(|p| percent(p)).sum();
+            let mut persons: Vec<String> = vec![];
+            let mut kept = false;
+            for person in data.persons.drain(..) {
+                if percent(&person).is_none() {
+                    persons.push(person);
+                } else if !kept {
+                    persons.push(format!("{}|{}", to, total));
+                    kept = true;
+                }
+            }
+            data.persons = persons;
+        }
+    }
+    for holiday in efforts.holidays.iter_mut() {
+        let mut workers: Vec<String> = vec![];
+        for worker in holiday.workers.drain(..) {
+            if !workers.contains(&worker) {
+                workers.push(worker);
+            }
+        }
+        holiday.workers = workers;
+    }
+    efforts.rebuild();
+
+    Ok(RenameReport {
+        from: from.to_string(),
+        to: to.to_string(),
+        merged,
+        cells,
+    })
+}
+
+/// The team with the most assigned percentage for each worker.
+pub fn main_teams(efforts: &EffortsDto) -> HashMap<String, Devs> {
+    let mut load: HashMap<String, BTreeMap<Devs, i32>> = HashMap::new();
+    for a in efforts.assignments() {
+        *load.entry(a.worker).or_default().entry(a.dev).or_default() += a.percent;
+    }
+    load.into_iter()
+        .filter_map(|(worker, teams)| {
+            // On a tie the first team in program order wins
+            let max = *teams.values().max()?;
+            let dev = teams.into_iter().find(|(_, v)| *v == max)?.0;
+            Some((worker, dev))
+        })
+        .collect()
+}
+
+/// A worker as listed by the roster panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RosterEntry {
+    pub name: String,
+    /// Team set in the roster, or else the one with most of the assignments.
+    pub team: Option<Devs>,
+    /// Whether `team` was set in the roster.
+    pub team_set: bool,
+    pub retired_from: Option<i32>,
+    /// Intended load, their own or else the one of the plan.
+    pub load: LoadTargetDto,
+    /// Whether `load` was set in the roster.
+    pub load_set: bool,
+    /// Non-empty cells with the worker.
+    pub assignments: usize,
+}
+
+/// The workers in footer order, with their roster settings.
+pub fn roster(efforts: &EffortsDto) -> Vec<RosterEntry> {
+    let main_teams = main_teams(efforts);
+    let assignments = efforts.assignments();
+    efforts
+        .worker_names
+        .iter()
+        .map(|name| {
+            let worker = efforts.worker(name);
+            let team = worker.and_then(|w| w.team);
+            RosterEntry {
+                name: name.clone(),
+                team: team.or_else(|| main_teams.get(name).copied()),
+                team_set: team.is_some(),
+                retired_from: worker.and_then(|w| w.retired_from),
+                load: efforts.load_target(name),
+                load_set: worker.is_some_and(|w| w.load.is_some()),
+                assignments: assignments.iter().filter(|a| &a.worker == name).count(),
+            }
+        })
+        .collect()
+}
+
+/// Drops the roster settings left empty.
+fn tidy(efforts: &mut EffortsDto) {
+    efforts.workers.retain(|w| !w.is_default());
+}
+
+/// Adds a worker at the end of the footer.
+///
+/// # Returns
+/// * `Err(String)` - If the name is not valid or is already a worker
+pub fn add_worker(efforts: &mut EffortsDto, name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name.contains('|') {
+        return Err(format!("Nome non valido: \"{}\"", name));
+    }
+    if efforts.worker_names.iter().any(|n| n == name) {
+        return Err(format!("\"{}\" è già un worker", name));
+    }
+    efforts.worker_names.push(name.to_string());
+    efforts.rebuild();
+    Ok(())
+}
+
+/// Hides a worker from the footer from the week of `from` on; `None` shows
+/// them again. Their assignments are kept.
+pub fn retire_worker(efforts: &mut EffortsDto, name: &str, from: Option<i32>) {
+    efforts.worker_mut(name).retired_from = from;
+    tidy(efforts);
+}
+
+/// Sets the team used to group the worker; `None` falls back to the team
+/// with most of their assignments.
+pub fn set_team(efforts: &mut EffortsDto, name: &str, team: Option<Devs>) {
+    efforts.worker_mut(name).team = team;
+    tidy(efforts);
+}
+
+/// Sets the intended load of a worker, or of the whole plan when `name` is
+/// `None`; a worker without one (`load` is `None`) follows the plan.
+pub fn set_load(efforts: &mut EffortsDto, name: Option<&str>, load: Option<LoadTargetDto>) {
+    match name {
+        Some(name) => {
+            efforts.worker_mut(name).load = load;
+            tidy(efforts);
+        }
+        None => efforts.load = load.unwrap_or_default(),
+    }
+}
+
+/// Moves a worker up (negative `delta`) or down in the footer.
+pub fn move_worker(efforts: &mut EffortsDto, name: &str, delta: i32) {
+    let Some(from) = efforts.worker_names.iter().position(|n| n == name) else {
+        return;
+    };
+    let to = (from as i32 + delta).clamp(0, efforts.worker_names.len() as i32 - 1) as usize;
+    let worker = efforts.worker_names.remove(from);
+    efforts.worker_names.insert(to, worker);
+    efforts.rebuild();
+}
+
+/// Orders the footer by team, in program order, keeping the current order
+/// within a team. Workers without a team go last.
+pub fn group_by_team(efforts: &mut EffortsDto) {
+    let teams: HashMap<String, Option<Devs>> = roster(efforts)
+        .into_iter()
+        .map(|entry| (entry.name, entry.team))
+        .collect();
+    efforts.worker_names.sort_by_key(|name| {
+        teams
+            .get(name)
+            .copied()
+            .flatten()
+            .map_or(usize::MAX, |t| t as usize)
+    });
+    efforts.rebuild();
+}
+
+/// Removes the workers without any assignment, and their roster settings.
+///
+/// Holidays are left as they are: a holiday of only removed workers would
+/// otherwise apply to everyone.
+///
+/// # Returns
+/// The removed workers.
+pub fn purge_unused(efforts: &mut EffortsDto) -> Vec<String> {
+    let used: Vec<String> = efforts
+        .assignments()
+        .into_iter()
+        .map(|a| a.worker)
+        .collect();
+    let (kept, removed): (Vec<String>, Vec<String>) = efforts
+        .worker_names
+        .drain(..)
+        .partition(|name| used.contains(name));
+    efforts.worker_names = kept;
+    efforts.workers.retain(|w| !removed.contains(&w.name));
+    efforts.rebuild();
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_workers() {
+        let known = ["Mario Rossi", "Anna Verdi", "Lucia Bianchi", "Paolo Neri"];
+        assert_eq!(suggest_workers("", known).len(), 4);
+        assert_eq!(suggest_workers("ERI|20", known), vec!["Paolo Neri"]);
+        assert_eq!(
+            suggest_workers("Anna Verdi|20", known),
+            Vec::<String>::new()
+        );
+        assert!(suggest_workers("xyz", known).is_empty());
+    }
+
+    #[test]
+    fn test_similar_workers() {
+        let known = ["Mario Rossi", "Maria Rossi", "Anna Verdi"];
+        assert_eq!(
+            similar_workers("Mario Rosi", known),
+            vec!["Mario Rossi", "Maria Rossi"]
+        );
+        assert_eq!(similar_workers("mario  rossi", known)[0], "Mario Rossi");
+        assert!(
+            similar_workers("Mario Rossi", known)
+                .iter()
+                .all(|n| n != "Mario Rossi")
+        );
+    }
+
+    #[test]
+    fn test_rename_and_merge_worker() {
+        let mut efforts = EffortsDto::default();
+        let datas = &mut efforts.projects[0].efforts[0].datas;
+        datas[0].persons = vec!["Mario Rosi|50".to_string(), "Anna|20".to_string()];
+        datas[1].persons = vec!["Mario Rossi|30".to_string()];
+        datas[2].persons = vec![
+            "Mario Rosi|50".to_string(),
+            "Anna|10".to_string(),
+            "Mario Rossi|30".to_string(),
+        ];
+        efforts.worker_names = vec![
+            "Mario Rosi".to_string(),
+            "Anna".to_string(),
+            "Mario Rossi".to_string(),
+        ];
+        efforts.rebuild();
+
+        let report = rename_worker(&mut efforts, "Anna", "Anna Verdi").unwrap();
+        assert!(!report.merged);
+        assert_eq!(report.cells, 2);
+        assert_eq!(
+            efforts.worker_names,
+            vec!["Mario Rosi", "Anna Verdi", "Mario Rossi"]
+        );
+
+        let report = rename_worker(&mut efforts, "Mario Rosi", "Mario Rossi").unwrap();
+        assert!(report.merged);
+        assert_eq!(efforts.worker_names, vec!["Anna Verdi", "Mario Rossi"]);
+        assert_eq!(
+            efforts.projects[0].efforts[0].datas[0].persons,
+            vec!["Mario Rossi|50", "Anna Verdi|20"]
+        );
+        assert_eq!(
+            efforts.projects[0].efforts[0].datas[2].persons,
+            vec!["Mario Rossi|80", "Anna Verdi|10"]
+        );
+        let week = efforts.projects[0].efforts[0].datas[0].week;
+        let sovra = efforts.sovra.iter().find(|s| s.week == week).unwrap();
+        assert_eq!(sovra.value.len(), 2);
+        assert!(sovra.value[1] > 0);
+
+        assert!(rename_worker(&mut efforts, "Nobody", "Anna").is_err());
+        assert!(rename_worker(&mut efforts, "Anna Verdi", "Anna|1").is_err());
+    }
+
+    #[test]
+    fn test_roster() {
+        let mut efforts = EffortsDto::default();
+        let datas = &mut efforts.projects[0].efforts;
+        datas[8].datas[0].persons = vec!["Anna|50".to_string()];
+        datas[0].datas[0].persons = vec!["Mario|50".to_string()];
+        efforts.rebuild();
+        add_worker(&mut efforts, "Paolo").unwrap();
+        assert!(add_worker(&mut efforts, "Mario").is_err());
+        assert_eq!(efforts.worker_names, vec!["Mario", "Anna", "Paolo"]);
+        assert!(efforts.sovra.iter().all(|s| s.value.len() == 3));
+
+        set_team(&mut efforts, "Paolo", Some(Devs::Hw));
+        group_by_team(&mut efforts);
+        assert_eq!(efforts.worker_names, vec!["Mario", "Paolo", "Anna"]);
+
+        move_worker(&mut efforts, "Anna", -5);
+        assert_eq!(efforts.worker_names, vec!["Anna", "Mario", "Paolo"]);
+        let week = efforts.projects[0].efforts[0].datas[0].week;
+        let sovra = efforts.sovra.iter().find(|s| s.week == week).unwrap();
+        assert_eq!(sovra.value, vec![50, 50, 0]);
+
+        retire_worker(&mut efforts, "Mario", Some(week));
+        assert!(efforts.worker("Mario").unwrap().is_retired(week));
+        retire_worker(&mut efforts, "Mario", None);
+        assert_eq!(efforts.worker("Mario"), None);
+
+        assert_eq!(purge_unused(&mut efforts), vec!["Paolo"]);
+        assert_eq!(efforts.worker_names, vec!["Anna", "Mario"]);
+        assert!(efforts.workers.is_empty());
+        assert_eq!(roster(&efforts)[0].team, Some(Devs::Pjm));
+    }
+
+    #[test]
+    fn test_set_load() {
+        let mut efforts = EffortsDto {
+            worker_names: vec!["Anna".to_string(), "Mario".to_string()],
+            ..Default::default()
+        };
+        let part_time = LoadTargetDto::new(50, 60).unwrap();
+        set_load(&mut efforts, Some("Anna"), Some(part_time));
+        set_load(
+            &mut efforts,
+            None,
+            Some(LoadTargetDto::new(100, 100).unwrap()),
+        );
+
+        let roster = roster(&efforts);
+        assert_eq!((roster[0].load, roster[0].load_set), (part_time, true));
+        assert_eq!(roster[1].load.target, 100);
+        assert!(!roster[1].load_set);
+
+        set_load(&mut efforts, Some("Anna"), None);
+        set_load(&mut efforts, None, None);
+        assert!(efforts.workers.is_empty());
+        assert_eq!(efforts.load_target("Anna"), LoadTargetDto::default());
+    }
+}
@@ -0,0 +1,356 @@
+//! Portfolio workspaces: several effort files shown and saved as one plan.
+//!
+//! A workspace is a JSON manifest listing effort files, with paths relative
+//! to the manifest:
+//!
+//! ```json
+//! { "files": ["sw/efforts.json", "hw/efforts.json"] }
+//! ```
+//!
+//! The projects of all files are loaded together, grouped by file, so that
+//! over-allocation is computed across departments. Closures and holidays of
+//! the files apply to the whole workspace; those added in the workspace, and
+//! the calendar, are stored in the manifest. On save every project
+//! goes back to the file it came from, and files without changes are not
+//! rewritten.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::file_io::{read_efforts_file, save_efforts_to_file};
+use crate::models::{
+    CalendarDto, CompactFile, EffortByPrjDto, EffortsDto, HolidayDto, LoadTargetDto, WorkerDto,
+};
+
+/// Content of a workspace manifest.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceManifest {
+    /// Effort files, relative to the manifest.
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub week_off: Vec<i32>,
+    #[serde(default)]
+    pub holidays: Vec<HolidayDto>,
+    #[serde(default)]
+    pub calendar: CalendarDto,
+    #[serde(default)]
+    pub load: LoadTargetDto,
+    #[serde(default)]
+    pub workers: Vec<WorkerDto>,
+}
+
+/// Whether the file is a workspace manifest rather than an effort file.
+///
+/// # Examples
+/// ```
+/// # use project_app::workspace::is_workspace_file;
+/// assert!(!is_workspace_file("nonexistent_file_12345.json"));
+/// ```
+pub fn is_workspace_file(path: &str) -> bool {
+    // The beginning is enough, effort files can be large
+    let mut buffer = [0u8; 64];
+    let Ok(read) = File::open(path).and_then(|mut file| file.read(&mut buffer)) else {
+        return false;
+    };
+    let head: String = String::from_utf8_lossy(&buffer[..read])
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .take(8)
+        .collect();
+    head == "{\"files\""
+}
+
+/// A workspace manifest opened from disk.
+pub struct Workspace {
+    pub path: String,
+    pub manifest: WorkspaceManifest,
+}
+
+impl Workspace {
+    /// Reads a workspace manifest.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the manifest
+    pub fn open(path: &str) -> Result<Workspace, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let manifest = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        Ok(Workspace {
+            path: path.to_string(),
+            manifest,
+        })
+    }
+
+    /// Path of a file of the workspace, resolved against the manifest folder.
+    pub fn resolve(&self, file: &str) -> PathBuf {
+        Path::new(&self.path)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(file)
+    }
+
+    /// Loads all files as one plan.
+    ///
+    /// Projects are renumbered in file order and tagged with their file in
+    /// [`EffortByPrjDto::source`]. Closures and holidays of the files are added
+    /// to those of the manifest.
+    ///
+    /// # Returns
+    /// * `Ok(EffortsDto)` - The plan of all files
+    /// * `Err(String)` - If a file is missing or can not be read
+    pub fn load(&self) -> Result<EffortsDto, String> {
+        let mut efforts = EffortsDto {
+            sovra: vec![],
+            week_off: self.manifest.week_off.clone(),
+            holidays: self.manifest.holidays.clone(),
+            calendar: self.manifest.calendar.clone(),
+            load: self.manifest.load,
+            worker_names: vec![],
+            workers: self.manifest.workers.clone(),
+            projects: vec![],
+        };
+
+        for file in self.manifest.files.iter() {
+            let part = read_efforts_file(&self.resolve(file).to_string_lossy())
+                .map_err(|e| format!("\"{}\": {}", file, e))?;
+            for mut prj in part.projects.into_iter() {
+                prj.set_project_id(efforts.projects.len() as i32);
+                prj.source = file.clone();
+                efforts.projects.push(prj);
+            }
+            for name in part.worker_names.into_iter() {
+                if !efforts.worker_names.contains(&name) {
+                    efforts.worker_names.push(name);
+                }
+            }
+            efforts.week_off.extend(part.week_off);
+            for holiday in part.holidays.into_iter() {
+                if !efforts.holidays.contains(&holiday) {
+                    efforts.holidays.push(holiday);
+                }
+            }
+        }
+
+        efforts.week_off.sort_unstable();
+        efforts.week_off.dedup();
+        efforts.holidays.sort_by_key(|h| h.day);
+        efforts.rebuild();
+        Ok(efforts)
+    }
+
+    /// Projects of the plan grouped by file, in manifest order.
+    ///
+    /// A project without a file (e.g. created after loading) goes to the file
+    /// of the project before it, or to the first file.
+    fn split(&self, efforts: &EffortsDto) -> Vec<(String, Vec<EffortByPrjDto>)> {
+        let mut groups: Vec<(String, Vec<EffortByPrjDto>)> = self
+            .manifest
+            .files
+            .iter()
+            .map(|file| (file.clone(), vec![]))
+            .collect();
+        let mut current = 0;
+        for prj in efforts.projects.iter() {
+            if let Some(index) = groups.iter().position(|(file, _)| *file == prj.source) {
+                current = index;
+            }
+            if let Some((_, projects)) = groups.get_mut(current) {
+                projects.push(prj.clone());
+            }
+        }
+        groups
+    }
+
+    /// Saves the plan back to the files its projects came from, and the
+    /// closures, holidays and calendar to the manifest.
+    ///
+    /// # Returns
+    /// * `Ok(files)` - The files that were written
+    /// * `Err(std::io::Error)` - The errors, one per file; the other files are
+    ///   still saved. A file that exists but can not be read is not overwritten.
+    pub fn save(&mut self, efforts: &EffortsDto) -> std::io::Result<Vec<String>> {
+        let mut written = vec![];
+        let mut errors = vec![];
+        let mut file_week_off = vec![];
+        let mut file_holidays = vec![];
+
+        for (file, projects) in self.split(efforts).into_iter() {
+            let path = self.resolve(&file).to_string_lossy().to_string();
+            let exists = Path::new(&path).exists();
+            let mut on_disk = if exists {
+                match read_efforts_file(&path) {
+                    Ok(on_disk) => on_disk,
+                    Err(e) => {
+                        errors.push(format!("\"{}\" non salvato: {}", file, e));
+                        continue;
+                    }
+                }
+            } else {
+                EffortsDto::default()
+            };
+            file_week_off.extend(on_disk.week_off.iter().copied());
+            file_holidays.extend(on_disk.holidays.iter().cloned());
+
+            let mut part = EffortsDto {
+                sovra: vec![],
+                week_off: on_disk.week_off.clone(),
+                holidays: on_disk.holidays.clone(),
+                calendar: on_disk.calendar.clone(),
+                load: on_disk.load,
+                worker_names: on_disk.worker_names.clone(),
+                workers: on_disk.workers.clone(),
+                projects,
+            };
+            for (id, prj) in part.projects.iter_mut().enumerate() {
+                prj.set_project_id(id as i32);
+                prj.source.clear();
+                // The GUI widens every project to the shown weeks
+                if let Some(old) = on_disk.projects.get_mut(id) {
+                    old.set_date(prj.start_week, prj.end_week);
+                }
+            }
+            part.rebuild();
+            on_disk.rebuild();
+
+            if exists && CompactFile::from(&part) == CompactFile::from(&on_disk) {
+                continue;
+            }
+            match save_efforts_to_file(&part, &path) {
+                Ok(()) => written.push(file),
+                Err(e) => errors.push(format!("\"{}\": {}", file, e)),
+            }
+        }
+
+        // What comes from the files stays in the files
+        let manifest = WorkspaceManifest {
+            week_off: efforts
+                .week_off
+                .iter()
+                .filter(|w| !file_week_off.contains(w))
+                .copied()
+                .collect(),
+            holidays: efforts
+                .holidays
+                .iter()
+                .filter(|h| !file_holidays.contains(h))
+                .cloned()
+                .collect(),
+            calendar: efforts.calendar.clone(),
+            load: efforts.load,
+            workers: efforts.workers.clone(),
+            ..self.manifest.clone()
+        };
+        if manifest != self.manifest {
+            let json = serde_json::to_string_pretty(&manifest).unwrap();
+            match std::fs::write(&self.path, json) {
+                Ok(()) => {
+                    self.manifest = manifest;
+                    written.push(self.path.clone());
+                }
+                Err(e) => errors.push(format!("\"{}\": {}", self.path, e)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(written)
+        } else {
+            Err(std::io::Error::other(errors.join("\n")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_io::save_efforts_to_file;
+    use std::fs;
+
+    fn department(name: &str, worker: &str) -> EffortsDto {
+        let mut efforts = EffortsDto::default();
+        efforts.projects[0].text = name.to_string();
+        efforts.projects[0].efforts[0].datas[0].persons = vec![format!("{}|60", worker)];
+        efforts.rebuild();
+        efforts
+    }
+
+    #[test]
+    fn test_load_and_save_back() {
+        let manifest = "test_workspace_manifest.json";
+        let (sw, hw) = ("test_workspace_sw.json", "test_workspace_hw.json");
+        save_efforts_to_file(&department("Alpha", "Mario"), sw).unwrap();
+        save_efforts_to_file(&department("Beta", "Mario"), hw).unwrap();
+        fs::write(
+            manifest,
+            format!("{{ \"files\": [\"{}\", \"{}\"] }}", sw, hw),
+        )
+        .unwrap();
+        assert!(is_workspace_file(manifest));
+        assert!(!is_workspace_file(sw));
+
+        let mut workspace = Workspace::open(manifest).unwrap();
+        let mut efforts = workspace.load().unwrap();
+        let ids: Vec<i32> = efforts.projects.iter().map(|p| p.project).collect();
+        assert_eq!(ids, vec![0, 1]);
+        assert_eq!(efforts.projects[1].source, hw);
+
+        // 60% in each department: over-allocated across the files
+        assert_eq!(efforts.worker_names, vec!["Mario"]);
+        assert_eq!(efforts.sovra[0].value[0], 120);
+
+        // Only the file of the edited project is written
+        efforts.projects[1].efforts[0].datas[0].persons = vec!["Mario|40".to_string()];
+        let written = workspace.save(&efforts).unwrap();
+        assert_eq!(written, vec![hw.to_string()]);
+
+        let saved = read_efforts_file(hw).unwrap();
+        assert_eq!(saved.projects[0].project, 0);
+        assert_eq!(
+            saved.projects[0].efforts[0].datas[0].persons,
+            vec!["Mario|40"]
+        );
+        assert!(!fs::read_to_string(hw).unwrap().contains("source"));
+
+        for file in [manifest, sw, hw] {
+            let _ = fs::remove_file(file);
+        }
+    }
+
+    #[test]
+    fn test_unreadable_file_is_not_overwritten() {
+        let manifest = "test_workspace_broken_manifest.json";
+        let (sw, hw) = (
+            "test_workspace_broken_sw.json",
+            "test_workspace_broken_hw.json",
+        );
+        save_efforts_to_file(&department("Alpha", "Mario"), sw).unwrap();
+        save_efforts_to_file(&department("Beta", "Anna"), hw).unwrap();
+        fs::write(
+            manifest,
+            format!("{{ \"files\": [\"{}\", \"{}\"] }}", sw, hw),
+        )
+        .unwrap();
+        let mut workspace = Workspace::open(manifest).unwrap();
+        let mut efforts = workspace.load().unwrap();
+
+        // Broken by hand after loading
+        fs::write(hw, "{ \"projects\": [").unwrap();
+        let error = workspace.load().unwrap_err();
+        assert!(error.starts_with(&format!("\"{}\"", hw)));
+
+        efforts.projects[0].efforts[0].datas[0].persons = vec!["Mario|40".to_string()];
+        efforts.projects[1].efforts[0].datas[0].persons = vec!["Anna|40".to_string()];
+        let error = workspace.save(&efforts).unwrap_err().to_string();
+        assert!(error.contains(hw));
+        assert_eq!(fs::read_to_string(hw).unwrap(), "{ \"projects\": [");
+        assert_eq!(
+            read_efforts_file(sw).unwrap().projects[0].efforts[0].datas[0].persons,
+            vec!["Mario|40"]
+        );
+
+        for file in [manifest, sw, hw] {
+            let _ = fs::remove_file(file);
+        }
+    }
+}